@@ -16,13 +16,19 @@ bindgen \
     --allowlist-type pf_status \
     --allowlist-type pfioc_rule \
     --allowlist-type pfioc_pooladdr \
+    --allowlist-type pfioc_ruleset \
     --allowlist-type pfioc_trans \
+    --allowlist-type pfioc_table \
+    --allowlist-type pfr_astats \
+    --allowlist-type pfr_tstats \
     --allowlist-type pfioc_states \
     --allowlist-type pfioc_state_kill \
     --allowlist-type pfioc_iface \
     --allowlist-type pfi_kif \
     --allowlist-var PF_.* \
     --allowlist-var PFRULE_.* \
+    --allowlist-var PFR_.* \
+    --allowlist-var PFI_AFLAG_.* \
     --default-enum-style rust \
     --with-derive-custom ".*=::zerocopy::FromZeros,::zerocopy::Immutable,::zerocopy::KnownLayout" \
     -o ./src/ffi/pfvar.rs "$pfvar_h_path" -- \
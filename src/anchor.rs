@@ -6,7 +6,9 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::ffi;
+use crate::{
+    AddrFamily, Direction, Endpoint, ErrorInternal, Interface, Proto, Result, RulesetKind, ffi,
+};
 
 /// Enum describing the kinds of anchors
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -28,3 +30,212 @@ impl From<AnchorKind> for u8 {
         }
     }
 }
+
+/// An anchor in PF, identified by its full path, together with the number of rules of each kind
+/// it contains.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnchorInfo {
+    pub(crate) path: String,
+    pub(crate) filter_rules: u32,
+    pub(crate) nat_rules: u32,
+    pub(crate) redirect_rules: u32,
+    pub(crate) scrub_rules: u32,
+}
+
+impl AnchorInfo {
+    /// Full path of the anchor, with nested anchors separated by `/`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the name of the anchor, the last component of its path.
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    /// Returns the number of rules of the given kind in the anchor.
+    pub fn rule_count(&self, kind: RulesetKind) -> u32 {
+        match kind {
+            RulesetKind::Filter => self.filter_rules,
+            RulesetKind::Nat => self.nat_rules,
+            RulesetKind::Redirect => self.redirect_rules,
+            RulesetKind::Scrub => self.scrub_rules,
+        }
+    }
+}
+
+/// An anchor together with all anchors nested inside it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnchorTree {
+    pub anchor: AnchorInfo,
+    pub children: Vec<AnchorTree>,
+}
+
+impl AnchorTree {
+    /// Returns the anchor at the root of this tree followed by all its nested anchors, depth
+    /// first.
+    pub fn iter(&self) -> impl Iterator<Item = &AnchorInfo> {
+        let children: Box<dyn Iterator<Item = &AnchorInfo>> =
+            Box::new(self.children.iter().flat_map(AnchorTree::iter));
+        std::iter::once(&self.anchor).chain(children)
+    }
+}
+
+/// A rule in the main ruleset that calls into an anchor, together with the criteria a packet has
+/// to match for the anchor to be evaluated.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnchorHook {
+    nr: u32,
+    anchor: String,
+    direction: Direction,
+    interface: Interface,
+    af: AddrFamily,
+    proto: Proto,
+    from: Endpoint,
+    to: Endpoint,
+    quick: bool,
+}
+
+impl AnchorHook {
+    /// Position of the anchor rule in the main ruleset, in the order PF evaluates the rules.
+    pub fn nr(&self) -> u32 {
+        self.nr
+    }
+
+    /// Path of the anchor being called. May end in `/*` to call all anchors nested inside it.
+    pub fn anchor(&self) -> &str {
+        &self.anchor
+    }
+
+    /// Accessor for `direction`
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Accessor for `interface`
+    pub fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    /// Accessor for `af`
+    pub fn af(&self) -> AddrFamily {
+        self.af
+    }
+
+    /// Accessor for `proto`
+    pub fn proto(&self) -> Proto {
+        self.proto
+    }
+
+    /// Accessor for `from`
+    pub fn from(&self) -> Endpoint {
+        self.from
+    }
+
+    /// Accessor for `to`
+    pub fn to(&self) -> Endpoint {
+        self.to
+    }
+
+    /// Accessor for `quick`
+    pub fn quick(&self) -> bool {
+        self.quick
+    }
+}
+
+impl TryFrom<ffi::pfvar::pfioc_rule> for AnchorHook {
+    type Error = crate::Error;
+
+    fn try_from(pfioc_rule: ffi::pfvar::pfioc_rule) -> Result<Self> {
+        let rule = pfioc_rule.rule;
+        let af = AddrFamily::try_from(rule.af)?;
+        Ok(AnchorHook {
+            nr: pfioc_rule.nr,
+            anchor: crate::cstr_to_string(&pfioc_rule.anchor_call)
+                .map_err(ErrorInternal::InvalidAnchorName)?,
+            direction: Direction::try_from(rule.direction)?,
+            interface: Interface::try_from_rule(&rule)?,
+            af,
+            proto: Proto::from(rule.proto),
+            from: Endpoint::try_from_raw(&rule.src, af)?,
+            to: Endpoint::try_from_raw(&rule.dst, af)?,
+            quick: rule.quick != 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ip, Port, conversion::TryCopyTo};
+    use std::net::Ipv4Addr;
+    use zerocopy::FromZeros;
+
+    fn anchor(path: &str) -> AnchorInfo {
+        AnchorInfo {
+            path: path.to_owned(),
+            filter_rules: 0,
+            nat_rules: 0,
+            redirect_rules: 0,
+            scrub_rules: 0,
+        }
+    }
+
+    fn tree(path: &str, children: Vec<AnchorTree>) -> AnchorTree {
+        AnchorTree {
+            anchor: anchor(path),
+            children,
+        }
+    }
+
+    #[test]
+    fn anchor_name() {
+        assert_eq!(anchor("com.apple").name(), "com.apple");
+        assert_eq!(
+            anchor("com.apple/250.ApplicationFirewall").name(),
+            "250.ApplicationFirewall"
+        );
+    }
+
+    #[test]
+    fn iter_tree_depth_first() {
+        let testee = tree(
+            "a",
+            vec![
+                tree("a/b", vec![tree("a/b/c", vec![])]),
+                tree("a/d", vec![]),
+            ],
+        );
+        let paths: Vec<&str> = testee.iter().map(AnchorInfo::path).collect();
+        assert_eq!(paths, ["a", "a/b", "a/b/c", "a/d"]);
+    }
+
+    #[test]
+    fn anchor_hook_from_pfioc_rule() {
+        let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
+        pfioc_rule.nr = 2;
+        "com.apple/*"
+            .try_copy_to(&mut pfioc_rule.anchor_call)
+            .unwrap();
+        pfioc_rule.rule.direction = Direction::Out.into();
+        pfioc_rule.rule.quick = 1;
+        pfioc_rule.rule.af = AddrFamily::Ipv4.into();
+        pfioc_rule.rule.proto = Proto::Tcp.into();
+        Interface::from("en0")
+            .try_copy_to(&mut pfioc_rule.rule.ifname)
+            .unwrap();
+        let to = Endpoint::new(Ipv4Addr::new(10, 0, 0, 1), Port::from(443));
+        to.try_copy_to(&mut pfioc_rule.rule.dst).unwrap();
+
+        let hook = AnchorHook::try_from(pfioc_rule).unwrap();
+        assert_eq!(hook.nr(), 2);
+        assert_eq!(hook.anchor(), "com.apple/*");
+        assert_eq!(hook.direction(), Direction::Out);
+        assert_eq!(hook.interface(), &Interface::from("en0"));
+        assert_eq!(hook.af(), AddrFamily::Ipv4);
+        assert_eq!(hook.proto(), Proto::Tcp);
+        assert_eq!(hook.from(), Endpoint::new(Ip::Any, Port::Any));
+        assert_eq!(hook.to(), to);
+        assert!(hook.quick());
+    }
+}
@@ -59,6 +59,40 @@ ioctl!(readwrite pf_kill_states with b'D', 41; pfvar::pfioc_state_kill);
 ioctl!(readwrite pf_begin_addrs with b'D', 51; pfvar::pfioc_pooladdr);
 // DIOCADDADDR
 ioctl!(readwrite pf_add_addr with b'D', 52; pfvar::pfioc_pooladdr);
+// DIOCGETADDRS
+ioctl!(readwrite pf_get_addrs with b'D', 53; pfvar::pfioc_pooladdr);
+// DIOCGETADDR
+ioctl!(readwrite pf_get_addr with b'D', 54; pfvar::pfioc_pooladdr);
+// DIOCGETRULESETS
+ioctl!(readwrite pf_get_rulesets with b'D', 58; pfvar::pfioc_ruleset);
+// DIOCGETRULESET
+ioctl!(readwrite pf_get_ruleset with b'D', 59; pfvar::pfioc_ruleset);
+// DIOCRADDTABLES
+ioctl!(readwrite pf_add_tables with b'D', 61; pfvar::pfioc_table);
+// DIOCRDELTABLES
+ioctl!(readwrite pf_delete_tables with b'D', 62; pfvar::pfioc_table);
+// DIOCRGETTABLES
+ioctl!(readwrite pf_get_tables with b'D', 63; pfvar::pfioc_table);
+// DIOCRGETTSTATS
+ioctl!(readwrite pf_get_table_stats with b'D', 64; pfvar::pfioc_table);
+// DIOCRCLRADDRS
+ioctl!(readwrite pf_clear_table_addrs with b'D', 66; pfvar::pfioc_table);
+// DIOCRADDADDRS
+ioctl!(readwrite pf_add_table_addrs with b'D', 67; pfvar::pfioc_table);
+// DIOCRDELADDRS
+ioctl!(readwrite pf_delete_table_addrs with b'D', 68; pfvar::pfioc_table);
+// DIOCRSETADDRS
+ioctl!(readwrite pf_set_table_addrs with b'D', 69; pfvar::pfioc_table);
+// DIOCRGETADDRS
+ioctl!(readwrite pf_get_table_addrs with b'D', 70; pfvar::pfioc_table);
+// DIOCRGETASTATS
+ioctl!(readwrite pf_get_table_addr_stats with b'D', 71; pfvar::pfioc_table);
+// DIOCRCLRASTATS
+ioctl!(readwrite pf_clear_table_addr_stats with b'D', 72; pfvar::pfioc_table);
+// DIOCRTSTADDRS
+ioctl!(readwrite pf_test_table_addrs with b'D', 73; pfvar::pfioc_table);
+// DIOCRINADEFINE
+ioctl!(readwrite pf_define_table with b'D', 77; pfvar::pfioc_table);
 // DIOCXBEGIN
 ioctl!(readwrite pf_begin_trans with b'D', 81; pfvar::pfioc_trans);
 // DIOCXCOMMIT
@@ -51,6 +51,11 @@ pub const PF_LOG: u32 = 1;
 pub const PF_LOG_ALL: u32 = 2;
 pub const PF_LOG_SOCKET_LOOKUP: u32 = 4;
 pub const PF_TABLE_NAME_SIZE: u32 = 32;
+pub const PFI_AFLAG_NETWORK: u32 = 1;
+pub const PFI_AFLAG_BROADCAST: u32 = 2;
+pub const PFI_AFLAG_PEER: u32 = 4;
+pub const PFI_AFLAG_MODEMASK: u32 = 7;
+pub const PFI_AFLAG_NOALIAS: u32 = 8;
 pub const PF_OSFP_EXPANDED: u32 = 1;
 pub const PF_OSFP_GENERIC: u32 = 2;
 pub const PF_OSFP_NODETAIL: u32 = 4;
@@ -119,12 +124,29 @@ pub const PF_TAG_NAME_STACK_DROP: &[u8; 28] = b"com.apple.pf.stack_drop_tag\0";
 pub const PF_THRESHOLD_MULT: u32 = 1000;
 pub const PF_THRESHOLD_MAX: u32 = 4294967;
 pub const PF_RESERVED_ANCHOR: &[u8; 4] = b"_pf\0";
+pub const PFR_TFLAG_PERSIST: u32 = 1;
+pub const PFR_TFLAG_CONST: u32 = 2;
+pub const PFR_TFLAG_ACTIVE: u32 = 4;
+pub const PFR_TFLAG_INACTIVE: u32 = 8;
+pub const PFR_TFLAG_REFERENCED: u32 = 16;
+pub const PFR_TFLAG_REFDANCHOR: u32 = 32;
+pub const PFR_TFLAG_USRMASK: u32 = 3;
+pub const PFR_TFLAG_SETMASK: u32 = 60;
+pub const PFR_TFLAG_ALLMASK: u32 = 63;
 pub const PF_DPORT_RANGE: u32 = 1;
 pub const PF_RPORT_RANGE: u32 = 2;
 pub const PF_ALTQ_BW_ABSOLUTE: u32 = 1;
 pub const PF_ALTQ_BW_PERCENT: u32 = 2;
 pub const PF_ALTQF_TBR: u32 = 1;
 pub const PF_ALTQ_QRF_WEIGHT: u32 = 1;
+pub const PFR_FLAG_ATOMIC: u32 = 1;
+pub const PFR_FLAG_DUMMY: u32 = 2;
+pub const PFR_FLAG_FEEDBACK: u32 = 4;
+pub const PFR_FLAG_CLSTATS: u32 = 8;
+pub const PFR_FLAG_ADDRSTOO: u32 = 16;
+pub const PFR_FLAG_REPLACE: u32 = 32;
+pub const PFR_FLAG_ALLRSETS: u32 = 64;
+pub const PFR_FLAG_ALLMASK: u32 = 127;
 pub type __uint8_t = ::std::os::raw::c_uchar;
 pub type __uint16_t = ::std::os::raw::c_ushort;
 pub type __int32_t = ::std::os::raw::c_int;
@@ -1561,6 +1583,231 @@ const _: () = {
     :: zerocopy :: Immutable,
     :: zerocopy :: KnownLayout,
 )]
+pub struct pfr_table {
+    pub pfrt_anchor: [::std::os::raw::c_char; 1024usize],
+    pub pfrt_name: [::std::os::raw::c_char; 32usize],
+    pub pfrt_flags: u_int32_t,
+    pub pfrt_fback: u_int8_t,
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of pfr_table"][::std::mem::size_of::<pfr_table>() - 1064usize];
+    ["Alignment of pfr_table"][::std::mem::align_of::<pfr_table>() - 4usize];
+    ["Offset of field: pfr_table::pfrt_anchor"]
+        [::std::mem::offset_of!(pfr_table, pfrt_anchor) - 0usize];
+    ["Offset of field: pfr_table::pfrt_name"]
+        [::std::mem::offset_of!(pfr_table, pfrt_name) - 1024usize];
+    ["Offset of field: pfr_table::pfrt_flags"]
+        [::std::mem::offset_of!(pfr_table, pfrt_flags) - 1056usize];
+    ["Offset of field: pfr_table::pfrt_fback"]
+        [::std::mem::offset_of!(pfr_table, pfrt_fback) - 1060usize];
+};
+pub const PFR_FB_NONE: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_NONE;
+pub const PFR_FB_MATCH: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_MATCH;
+pub const PFR_FB_ADDED: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_ADDED;
+pub const PFR_FB_DELETED: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_DELETED;
+pub const PFR_FB_CHANGED: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_CHANGED;
+pub const PFR_FB_CLEARED: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_CLEARED;
+pub const PFR_FB_DUPLICATE: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_DUPLICATE;
+pub const PFR_FB_NOTMATCH: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_NOTMATCH;
+pub const PFR_FB_CONFLICT: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_CONFLICT;
+pub const PFR_FB_MAX: _bindgen_ty_18 = _bindgen_ty_18::PFR_FB_MAX;
+#[repr(u32)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    :: zerocopy :: FromZeros,
+    :: zerocopy :: Immutable,
+    :: zerocopy :: KnownLayout,
+)]
+pub enum _bindgen_ty_18 {
+    PFR_FB_NONE = 0,
+    PFR_FB_MATCH = 1,
+    PFR_FB_ADDED = 2,
+    PFR_FB_DELETED = 3,
+    PFR_FB_CHANGED = 4,
+    PFR_FB_CLEARED = 5,
+    PFR_FB_DUPLICATE = 6,
+    PFR_FB_NOTMATCH = 7,
+    PFR_FB_CONFLICT = 8,
+    PFR_FB_MAX = 9,
+}
+pub const PFR_DIR_IN: _bindgen_ty_19 = _bindgen_ty_19::PFR_DIR_IN;
+pub const PFR_DIR_OUT: _bindgen_ty_19 = _bindgen_ty_19::PFR_DIR_OUT;
+pub const PFR_DIR_MAX: _bindgen_ty_19 = _bindgen_ty_19::PFR_DIR_MAX;
+#[repr(u32)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    :: zerocopy :: FromZeros,
+    :: zerocopy :: Immutable,
+    :: zerocopy :: KnownLayout,
+)]
+pub enum _bindgen_ty_19 {
+    PFR_DIR_IN = 0,
+    PFR_DIR_OUT = 1,
+    PFR_DIR_MAX = 2,
+}
+pub const PFR_OP_BLOCK: _bindgen_ty_20 = _bindgen_ty_20::PFR_OP_BLOCK;
+pub const PFR_OP_PASS: _bindgen_ty_20 = _bindgen_ty_20::PFR_OP_PASS;
+pub const PFR_OP_ADDR_MAX: _bindgen_ty_20 = _bindgen_ty_20::PFR_OP_ADDR_MAX;
+pub const PFR_OP_TABLE_MAX: _bindgen_ty_20 = _bindgen_ty_20::PFR_OP_TABLE_MAX;
+#[repr(u32)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    :: zerocopy :: FromZeros,
+    :: zerocopy :: Immutable,
+    :: zerocopy :: KnownLayout,
+)]
+pub enum _bindgen_ty_20 {
+    PFR_OP_BLOCK = 0,
+    PFR_OP_PASS = 1,
+    PFR_OP_ADDR_MAX = 2,
+    PFR_OP_TABLE_MAX = 3,
+}
+#[repr(C)]
+#[derive(
+    Copy, Clone, :: zerocopy :: FromZeros, :: zerocopy :: Immutable, :: zerocopy :: KnownLayout,
+)]
+pub struct pfr_addr {
+    pub pfra_u: pfr_addr__bindgen_ty_1,
+    pub pfra_af: u_int8_t,
+    pub pfra_net: u_int8_t,
+    pub pfra_not: u_int8_t,
+    pub pfra_fback: u_int8_t,
+}
+#[repr(C)]
+#[derive(
+    Copy, Clone, :: zerocopy :: FromZeros, :: zerocopy :: Immutable, :: zerocopy :: KnownLayout,
+)]
+pub union pfr_addr__bindgen_ty_1 {
+    pub _pfra_ip4addr: in_addr,
+    pub _pfra_ip6addr: in6_addr,
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of pfr_addr__bindgen_ty_1"][::std::mem::size_of::<pfr_addr__bindgen_ty_1>() - 16usize];
+    ["Alignment of pfr_addr__bindgen_ty_1"]
+        [::std::mem::align_of::<pfr_addr__bindgen_ty_1>() - 4usize];
+    ["Offset of field: pfr_addr__bindgen_ty_1::_pfra_ip4addr"]
+        [::std::mem::offset_of!(pfr_addr__bindgen_ty_1, _pfra_ip4addr) - 0usize];
+    ["Offset of field: pfr_addr__bindgen_ty_1::_pfra_ip6addr"]
+        [::std::mem::offset_of!(pfr_addr__bindgen_ty_1, _pfra_ip6addr) - 0usize];
+};
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of pfr_addr"][::std::mem::size_of::<pfr_addr>() - 20usize];
+    ["Alignment of pfr_addr"][::std::mem::align_of::<pfr_addr>() - 4usize];
+    ["Offset of field: pfr_addr::pfra_u"][::std::mem::offset_of!(pfr_addr, pfra_u) - 0usize];
+    ["Offset of field: pfr_addr::pfra_af"][::std::mem::offset_of!(pfr_addr, pfra_af) - 16usize];
+    ["Offset of field: pfr_addr::pfra_net"][::std::mem::offset_of!(pfr_addr, pfra_net) - 17usize];
+    ["Offset of field: pfr_addr::pfra_not"][::std::mem::offset_of!(pfr_addr, pfra_not) - 18usize];
+    ["Offset of field: pfr_addr::pfra_fback"]
+        [::std::mem::offset_of!(pfr_addr, pfra_fback) - 19usize];
+};
+#[repr(C)]
+#[derive(
+    Copy, Clone, :: zerocopy :: FromZeros, :: zerocopy :: Immutable, :: zerocopy :: KnownLayout,
+)]
+pub struct pfr_astats {
+    pub pfras_a: pfr_addr,
+    pub pfras_packets: [[u_int64_t; 2usize]; 2usize],
+    pub pfras_bytes: [[u_int64_t; 2usize]; 2usize],
+    pub pfras_tzero: u_int64_t,
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of pfr_astats"][::std::mem::size_of::<pfr_astats>() - 96usize];
+    ["Alignment of pfr_astats"][::std::mem::align_of::<pfr_astats>() - 8usize];
+    ["Offset of field: pfr_astats::pfras_a"][::std::mem::offset_of!(pfr_astats, pfras_a) - 0usize];
+    ["Offset of field: pfr_astats::pfras_packets"]
+        [::std::mem::offset_of!(pfr_astats, pfras_packets) - 24usize];
+    ["Offset of field: pfr_astats::pfras_bytes"]
+        [::std::mem::offset_of!(pfr_astats, pfras_bytes) - 56usize];
+    ["Offset of field: pfr_astats::pfras_tzero"]
+        [::std::mem::offset_of!(pfr_astats, pfras_tzero) - 88usize];
+};
+pub const PFR_REFCNT_RULE: _bindgen_ty_21 = _bindgen_ty_21::PFR_REFCNT_RULE;
+pub const PFR_REFCNT_ANCHOR: _bindgen_ty_21 = _bindgen_ty_21::PFR_REFCNT_ANCHOR;
+pub const PFR_REFCNT_MAX: _bindgen_ty_21 = _bindgen_ty_21::PFR_REFCNT_MAX;
+#[repr(u32)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    :: zerocopy :: FromZeros,
+    :: zerocopy :: Immutable,
+    :: zerocopy :: KnownLayout,
+)]
+pub enum _bindgen_ty_21 {
+    PFR_REFCNT_RULE = 0,
+    PFR_REFCNT_ANCHOR = 1,
+    PFR_REFCNT_MAX = 2,
+}
+#[repr(C)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    :: zerocopy :: FromZeros,
+    :: zerocopy :: Immutable,
+    :: zerocopy :: KnownLayout,
+)]
+pub struct pfr_tstats {
+    pub pfrts_t: pfr_table,
+    pub pfrts_packets: [[u_int64_t; 3usize]; 2usize],
+    pub pfrts_bytes: [[u_int64_t; 3usize]; 2usize],
+    pub pfrts_match: u_int64_t,
+    pub pfrts_nomatch: u_int64_t,
+    pub pfrts_tzero: u_int64_t,
+    pub pfrts_cnt: ::std::os::raw::c_int,
+    pub pfrts_refcnt: [::std::os::raw::c_int; 2usize],
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of pfr_tstats"][::std::mem::size_of::<pfr_tstats>() - 1200usize];
+    ["Alignment of pfr_tstats"][::std::mem::align_of::<pfr_tstats>() - 8usize];
+    ["Offset of field: pfr_tstats::pfrts_t"][::std::mem::offset_of!(pfr_tstats, pfrts_t) - 0usize];
+    ["Offset of field: pfr_tstats::pfrts_packets"]
+        [::std::mem::offset_of!(pfr_tstats, pfrts_packets) - 1064usize];
+    ["Offset of field: pfr_tstats::pfrts_bytes"]
+        [::std::mem::offset_of!(pfr_tstats, pfrts_bytes) - 1112usize];
+    ["Offset of field: pfr_tstats::pfrts_match"]
+        [::std::mem::offset_of!(pfr_tstats, pfrts_match) - 1160usize];
+    ["Offset of field: pfr_tstats::pfrts_nomatch"]
+        [::std::mem::offset_of!(pfr_tstats, pfrts_nomatch) - 1168usize];
+    ["Offset of field: pfr_tstats::pfrts_tzero"]
+        [::std::mem::offset_of!(pfr_tstats, pfrts_tzero) - 1176usize];
+    ["Offset of field: pfr_tstats::pfrts_cnt"]
+        [::std::mem::offset_of!(pfr_tstats, pfrts_cnt) - 1184usize];
+    ["Offset of field: pfr_tstats::pfrts_refcnt"]
+        [::std::mem::offset_of!(pfr_tstats, pfrts_refcnt) - 1188usize];
+};
+#[repr(C)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    :: zerocopy :: FromZeros,
+    :: zerocopy :: Immutable,
+    :: zerocopy :: KnownLayout,
+)]
 pub struct pfi_kif {
     pub pfik_name: [::std::os::raw::c_char; 16usize],
     pub pfik_packets: [[[u_int64_t; 2usize]; 2usize]; 2usize],
@@ -1806,6 +2053,29 @@ const _: () = {
     :: zerocopy :: Immutable,
     :: zerocopy :: KnownLayout,
 )]
+pub struct pfioc_ruleset {
+    pub nr: u_int32_t,
+    pub path: [::std::os::raw::c_char; 1024usize],
+    pub name: [::std::os::raw::c_char; 64usize],
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of pfioc_ruleset"][::std::mem::size_of::<pfioc_ruleset>() - 1092usize];
+    ["Alignment of pfioc_ruleset"][::std::mem::align_of::<pfioc_ruleset>() - 4usize];
+    ["Offset of field: pfioc_ruleset::nr"][::std::mem::offset_of!(pfioc_ruleset, nr) - 0usize];
+    ["Offset of field: pfioc_ruleset::path"][::std::mem::offset_of!(pfioc_ruleset, path) - 4usize];
+    ["Offset of field: pfioc_ruleset::name"]
+        [::std::mem::offset_of!(pfioc_ruleset, name) - 1028usize];
+};
+#[repr(C)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    :: zerocopy :: FromZeros,
+    :: zerocopy :: Immutable,
+    :: zerocopy :: KnownLayout,
+)]
 pub struct pfioc_trans {
     pub size: ::std::os::raw::c_int,
     pub esize: ::std::os::raw::c_int,
@@ -1855,6 +2125,52 @@ const _: () = {
     :: zerocopy :: Immutable,
     :: zerocopy :: KnownLayout,
 )]
+pub struct pfioc_table {
+    pub pfrio_table: pfr_table,
+    pub pfrio_buffer: *mut ::std::os::raw::c_void,
+    pub pfrio_esize: ::std::os::raw::c_int,
+    pub pfrio_size: ::std::os::raw::c_int,
+    pub pfrio_size2: ::std::os::raw::c_int,
+    pub pfrio_nadd: ::std::os::raw::c_int,
+    pub pfrio_ndel: ::std::os::raw::c_int,
+    pub pfrio_nchange: ::std::os::raw::c_int,
+    pub pfrio_flags: ::std::os::raw::c_int,
+    pub pfrio_ticket: u_int32_t,
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of pfioc_table"][::std::mem::size_of::<pfioc_table>() - 1104usize];
+    ["Alignment of pfioc_table"][::std::mem::align_of::<pfioc_table>() - 8usize];
+    ["Offset of field: pfioc_table::pfrio_table"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_table) - 0usize];
+    ["Offset of field: pfioc_table::pfrio_buffer"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_buffer) - 1064usize];
+    ["Offset of field: pfioc_table::pfrio_esize"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_esize) - 1072usize];
+    ["Offset of field: pfioc_table::pfrio_size"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_size) - 1076usize];
+    ["Offset of field: pfioc_table::pfrio_size2"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_size2) - 1080usize];
+    ["Offset of field: pfioc_table::pfrio_nadd"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_nadd) - 1084usize];
+    ["Offset of field: pfioc_table::pfrio_ndel"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_ndel) - 1088usize];
+    ["Offset of field: pfioc_table::pfrio_nchange"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_nchange) - 1092usize];
+    ["Offset of field: pfioc_table::pfrio_flags"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_flags) - 1096usize];
+    ["Offset of field: pfioc_table::pfrio_ticket"]
+        [::std::mem::offset_of!(pfioc_table, pfrio_ticket) - 1100usize];
+};
+#[repr(C)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    :: zerocopy :: FromZeros,
+    :: zerocopy :: Immutable,
+    :: zerocopy :: KnownLayout,
+)]
 pub struct pfioc_iface {
     pub pfiio_name: [::std::os::raw::c_char; 16usize],
     pub pfiio_buffer: *mut ::std::os::raw::c_void,
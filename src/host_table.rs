@@ -0,0 +1,310 @@
+// Copyright 2025 Mullvad VPN AB.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{Error, ErrorInternal, PfCtl, Result, TableChanges, TableFlags, TableName};
+use std::{
+    collections::BTreeSet,
+    net::{IpAddr, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+/// Resolves host names into addresses for a `HostTable`.
+pub trait Resolver {
+    /// Returns all addresses of `host`.
+    fn resolve(&self, host: &str) -> std::io::Result<Vec<IpAddr>>;
+}
+
+/// `Resolver` using the resolver of the operating system.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve(&self, host: &str) -> std::io::Result<Vec<IpAddr>> {
+        Ok((host, 0)
+            .to_socket_addrs()?
+            .map(|socket_addr| socket_addr.ip())
+            .collect())
+    }
+}
+
+/// A PF table kept in sync with the addresses a set of host names resolve to.
+///
+/// Each refresh resolves all hosts and atomically replaces the contents of the table with the
+/// result. If any host fails to resolve, the table is left untouched, so a temporary DNS outage
+/// does not empty it.
+#[derive(Debug)]
+pub struct HostTable<R = SystemResolver> {
+    anchor: String,
+    name: TableName,
+    hosts: Vec<String>,
+    resolver: R,
+    refresh_interval: Duration,
+    addrs: BTreeSet<IpAddr>,
+    last_refresh: Option<Instant>,
+    created: bool,
+}
+
+impl HostTable<SystemResolver> {
+    /// Returns a `HostTable` for the table `name` in `anchor`, resolving `hosts` with the
+    /// resolver of the operating system.
+    pub fn new<S: Into<String>>(
+        anchor: &str,
+        name: TableName,
+        hosts: impl IntoIterator<Item = S>,
+    ) -> Self {
+        Self::with_resolver(anchor, name, hosts, SystemResolver)
+    }
+}
+
+impl<R: Resolver> HostTable<R> {
+    /// Default time between two refreshes done by `refresh_if_due`.
+    pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+    /// Same as `new`, but resolves `hosts` with `resolver`.
+    pub fn with_resolver<S: Into<String>>(
+        anchor: &str,
+        name: TableName,
+        hosts: impl IntoIterator<Item = S>,
+        resolver: R,
+    ) -> Self {
+        HostTable {
+            anchor: anchor.to_owned(),
+            name,
+            hosts: hosts.into_iter().map(Into::into).collect(),
+            resolver,
+            refresh_interval: Self::DEFAULT_REFRESH_INTERVAL,
+            addrs: BTreeSet::new(),
+            last_refresh: None,
+            created: false,
+        }
+    }
+
+    /// Sets the time between two refreshes done by `refresh_if_due`.
+    pub fn set_refresh_interval(&mut self, refresh_interval: Duration) {
+        self.refresh_interval = refresh_interval;
+    }
+
+    /// Accessor for `name`
+    pub fn name(&self) -> TableName {
+        self.name
+    }
+
+    /// Accessor for `hosts`
+    pub fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+
+    /// Returns the addresses the table was last set to. Empty before the first refresh.
+    pub fn addrs(&self) -> &BTreeSet<IpAddr> {
+        &self.addrs
+    }
+
+    /// Returns when `refresh_if_due` will refresh the table next, or `None` if it has never been
+    /// refreshed, in which case it is due immediately.
+    pub fn next_refresh(&self) -> Option<Instant> {
+        self.last_refresh
+            .map(|last_refresh| last_refresh + self.refresh_interval)
+    }
+
+    /// Resolves all hosts and replaces the contents of the table with the result. The table is
+    /// created with `TableFlags::PERSIST`, if it does not exist, on the first refresh and again
+    /// on the refresh after replacing the addresses failed.
+    ///
+    /// Returns the addresses added and removed compared to the previous refresh, see
+    /// `HostTableUpdate`.
+    pub fn refresh(&mut self, pf: &mut PfCtl) -> Result<HostTableUpdate> {
+        let addrs = self.resolve()?;
+        if !self.created {
+            pf.create_table(&self.anchor, self.name.as_str(), TableFlags::PERSIST)?;
+            self.created = true;
+        }
+        let table_changes = pf
+            .table_replace_addrs(&self.anchor, self.name.as_str(), addrs.iter().copied())
+            .inspect_err(|_| self.created = false)?;
+        Ok(self.update(addrs, table_changes, Instant::now()))
+    }
+
+    /// Same as `refresh`, but only refreshes the table if the refresh interval has passed since
+    /// the last refresh. Returns `None` if the table was not refreshed.
+    pub fn refresh_if_due(&mut self, pf: &mut PfCtl) -> Result<Option<HostTableUpdate>> {
+        if self.is_due(Instant::now()) {
+            self.refresh(pf).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.next_refresh()
+            .is_none_or(|next_refresh| now >= next_refresh)
+    }
+
+    /// Resolves all hosts, failing if any of them can not be resolved.
+    fn resolve(&self) -> Result<BTreeSet<IpAddr>> {
+        let mut addrs = BTreeSet::new();
+        for host in &self.hosts {
+            let host_addrs = self
+                .resolver
+                .resolve(host)
+                .map_err(|e| Error::from(ErrorInternal::ResolveHost(host.clone(), e)))?;
+            addrs.extend(host_addrs);
+        }
+        Ok(addrs)
+    }
+
+    /// Records `addrs` as the current contents of the table, returning what changed.
+    fn update(
+        &mut self,
+        addrs: BTreeSet<IpAddr>,
+        table_changes: TableChanges,
+        now: Instant,
+    ) -> HostTableUpdate {
+        let update = HostTableUpdate {
+            added: addrs.difference(&self.addrs).copied().collect(),
+            removed: self.addrs.difference(&addrs).copied().collect(),
+            table_changes,
+        };
+        self.addrs = addrs;
+        self.last_refresh = Some(now);
+        update
+    }
+}
+
+/// The addresses added to and removed from a `HostTable` by a refresh.
+///
+/// `added` and `removed` are relative to the addresses of the previous refresh, not to what the
+/// table held before this refresh. If something else edited the table in between, they differ
+/// from what PF changed, which `table_changes` reports.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct HostTableUpdate {
+    added: Vec<IpAddr>,
+    removed: Vec<IpAddr>,
+    table_changes: TableChanges,
+}
+
+impl HostTableUpdate {
+    /// Accessor for `added`
+    pub fn added(&self) -> &[IpAddr] {
+        &self.added
+    }
+
+    /// Accessor for `removed`
+    pub fn removed(&self) -> &[IpAddr] {
+        &self.removed
+    }
+
+    /// Accessor for `table_changes`
+    pub fn table_changes(&self) -> TableChanges {
+        self.table_changes
+    }
+
+    /// Returns true if the hosts resolved to the same addresses as on the previous refresh.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, collections::HashMap};
+
+    /// Resolver answering from a map that the tests can change between refreshes.
+    #[derive(Default)]
+    struct StubResolver(RefCell<HashMap<&'static str, Vec<IpAddr>>>);
+
+    impl StubResolver {
+        fn set(&self, host: &'static str, addrs: &[[u8; 4]]) {
+            let addrs = addrs.iter().map(|addr| IpAddr::from(*addr)).collect();
+            self.0.borrow_mut().insert(host, addrs);
+        }
+    }
+
+    impl Resolver for &StubResolver {
+        fn resolve(&self, host: &str) -> std::io::Result<Vec<IpAddr>> {
+            self.0
+                .borrow()
+                .get(host)
+                .cloned()
+                .ok_or_else(|| std::io::Error::other("unknown host"))
+        }
+    }
+
+    fn ip(addr: [u8; 4]) -> IpAddr {
+        IpAddr::from(addr)
+    }
+
+    #[test]
+    fn update_reports_added_and_removed() {
+        let resolver = StubResolver::default();
+        resolver.set("api.example.com", &[[192, 0, 2, 1], [192, 0, 2, 2]]);
+        resolver.set("updates.example.com", &[[192, 0, 2, 2]]);
+        let name = TableName::new("allowed").unwrap();
+        let mut table = HostTable::with_resolver(
+            "anchor",
+            name,
+            ["api.example.com", "updates.example.com"],
+            &resolver,
+        );
+
+        let now = Instant::now();
+        let update = table.update(table.resolve().unwrap(), TableChanges::default(), now);
+        assert_eq!(update.added(), &[ip([192, 0, 2, 1]), ip([192, 0, 2, 2])]);
+        assert!(update.removed().is_empty());
+
+        resolver.set("api.example.com", &[[192, 0, 2, 3]]);
+        let update = table.update(table.resolve().unwrap(), TableChanges::default(), now);
+        assert_eq!(update.added(), &[ip([192, 0, 2, 3])]);
+        assert_eq!(update.removed(), &[ip([192, 0, 2, 1])]);
+        assert_eq!(
+            table.addrs().iter().copied().collect::<Vec<_>>(),
+            &[ip([192, 0, 2, 2]), ip([192, 0, 2, 3])]
+        );
+
+        assert!(
+            table
+                .update(table.resolve().unwrap(), TableChanges::default(), now)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn resolve_fails_if_any_host_fails() {
+        let resolver = StubResolver::default();
+        resolver.set("api.example.com", &[[192, 0, 2, 1]]);
+        let name = TableName::new("allowed").unwrap();
+        let table = HostTable::with_resolver(
+            "anchor",
+            name,
+            ["api.example.com", "missing.example.com"],
+            &resolver,
+        );
+
+        let error = table.resolve().unwrap_err();
+        assert_eq!(error.kind(), crate::ErrorKind::ResolveHost);
+        assert_eq!(
+            error.to_string(),
+            "Unable to resolve host (missing.example.com)"
+        );
+    }
+
+    #[test]
+    fn refresh_is_due_after_interval() {
+        let resolver = StubResolver::default();
+        let name = TableName::new("allowed").unwrap();
+        let mut table = HostTable::with_resolver("anchor", name, Vec::<String>::new(), &resolver);
+        table.set_refresh_interval(Duration::from_secs(60));
+
+        let now = Instant::now();
+        assert!(table.is_due(now));
+        table.update(BTreeSet::new(), TableChanges::default(), now);
+        assert_eq!(table.next_refresh(), Some(now + Duration::from_secs(60)));
+        assert!(!table.is_due(now + Duration::from_secs(59)));
+        assert!(table.is_due(now + Duration::from_secs(60)));
+    }
+}
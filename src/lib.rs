@@ -64,7 +64,9 @@ use std::{
     fmt,
     fs::File,
     mem,
+    net::IpAddr,
     os::unix::io::{AsRawFd, RawFd},
+    time::{Duration, SystemTime},
 };
 
 pub use ipnetwork;
@@ -88,12 +90,26 @@ pub use crate::anchor::*;
 mod ruleset;
 pub use crate::ruleset::*;
 
+mod rule_position;
+pub use crate::rule_position::*;
+
+mod rule_stats;
+pub use crate::rule_stats::*;
+
 mod state;
 pub use crate::state::*;
 
+mod table;
+pub use crate::table::*;
+
+mod host_table;
+pub use crate::host_table::*;
+
 mod transaction;
 pub use crate::transaction::*;
 
+pub mod netlist;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -109,6 +125,16 @@ pub enum ErrorKind {
     InvalidInterfaceName,
     /// The supplied anchor name in not compatible with PF
     InvalidAnchorName,
+    /// The supplied table name is not compatible with PF
+    InvalidTableName,
+    /// A table address read from PF is not valid
+    InvalidTableAddress,
+    /// The supplied table flags can not be set by the user
+    InvalidTableFlags,
+    /// A list of networks could not be read or contains an invalid network
+    InvalidNetList,
+    /// A host name could not be resolved
+    ResolveHost,
     /// The supplied port is an invalid range
     InvalidPortRange,
     /// The supplied rule label is not compatible with PF.
@@ -118,11 +144,18 @@ pub enum ErrorKind {
     /// The direction is invalid
     InvalidDirection,
     /// The transport protocol is invalid
+    #[deprecated(note = "All protocol numbers are valid since the addition of `Proto::Other`")]
     InvalidTransportProtocol,
     /// The target state was already active
     StateAlreadyActive,
     /// This PF anchor does not exist
     AnchorDoesNotExist,
+    /// No rule in the anchor matches the given rule
+    RuleDoesNotExist,
+    /// The rule number of a rule position is out of range
+    InvalidRulePosition,
+    /// A rule read from PF uses features that can not be represented by this crate
+    UnsupportedRule,
     /// System returned an error during ioctl system call
     Ioctl,
 }
@@ -137,14 +170,23 @@ enum ErrorInternal {
     UninitializedFieldError(derive_builder::UninitializedFieldError),
     InvalidInterfaceName(&'static str),
     InvalidAnchorName(&'static str),
+    InvalidTableName(&'static str),
+    InvalidTableAddress(&'static str),
+    InvalidTableFlags(TableFlags),
+    InvalidNetList(usize, String),
+    NetListRead(std::io::Error),
+    ResolveHost(String, std::io::Error),
     InvalidPortRange,
     InvalidLabel(&'static str),
     InvalidAddressFamily(u8),
     InvalidDirection(u8),
-    InvalidTransportProtocol(u8),
     StateAlreadyActive,
     AnchorDoesNotExist,
+    RuleDoesNotExist,
+    InvalidRulePosition,
+    UnsupportedRule(String),
     Ioctl(std::io::Error),
+    RuleAtIndex(usize, Box<Error>),
 }
 
 impl Error {
@@ -157,14 +199,31 @@ impl Error {
             UninitializedFieldError(_) => ErrorKind::UninitializedFieldError,
             InvalidInterfaceName(..) => ErrorKind::InvalidInterfaceName,
             InvalidAnchorName(..) => ErrorKind::InvalidAnchorName,
+            InvalidTableName(..) => ErrorKind::InvalidTableName,
+            InvalidTableAddress(..) => ErrorKind::InvalidTableAddress,
+            InvalidTableFlags(_) => ErrorKind::InvalidTableFlags,
+            InvalidNetList(..) | NetListRead(_) => ErrorKind::InvalidNetList,
+            ResolveHost(..) => ErrorKind::ResolveHost,
             InvalidPortRange => ErrorKind::InvalidPortRange,
             InvalidLabel(..) => ErrorKind::InvalidLabel,
             InvalidAddressFamily(_) => ErrorKind::InvalidAddressFamily,
             InvalidDirection(_) => ErrorKind::InvalidDirection,
-            InvalidTransportProtocol(_) => ErrorKind::InvalidTransportProtocol,
             StateAlreadyActive => ErrorKind::StateAlreadyActive,
             AnchorDoesNotExist => ErrorKind::AnchorDoesNotExist,
+            RuleDoesNotExist => ErrorKind::RuleDoesNotExist,
+            InvalidRulePosition => ErrorKind::InvalidRulePosition,
+            UnsupportedRule(_) => ErrorKind::UnsupportedRule,
             Ioctl(_) => ErrorKind::Ioctl,
+            RuleAtIndex(_, ref error) => error.kind(),
+        }
+    }
+
+    /// Returns the index of the rule that failed, if this error comes from adding a batch of
+    /// rules, such as with `PfCtl::add_rules`.
+    pub fn rule_index(&self) -> Option<usize> {
+        match self.0 {
+            ErrorInternal::RuleAtIndex(index, _) => Some(index),
+            _ => None,
         }
     }
 }
@@ -186,16 +245,33 @@ impl fmt::Display for Error {
             UninitializedFieldError(inner) => inner.fmt(f),
             InvalidInterfaceName(reason) => write!(f, "Invalid interface name ({reason})"),
             InvalidAnchorName(reason) => write!(f, "Invalid anchor name ({reason})"),
+            InvalidTableName(reason) => write!(f, "Invalid table name ({reason})"),
+            InvalidTableAddress(reason) => write!(f, "Invalid table address ({reason})"),
+            InvalidTableFlags(flags) => {
+                write!(
+                    f,
+                    "Invalid table flags ({flags:?}), only PERSIST and CONST can be set"
+                )
+            }
+            InvalidNetList(line, entry) => {
+                write!(
+                    f,
+                    "Invalid network on line {line} of address list ({entry})"
+                )
+            }
+            NetListRead(_) => write!(f, "Unable to read address list"),
+            ResolveHost(host, _) => write!(f, "Unable to resolve host ({host})"),
             InvalidPortRange => write!(f, "Lower port is greater than upper port"),
             InvalidLabel(reason) => write!(f, "Invalid rule label ({reason}"),
             InvalidAddressFamily(family) => write!(f, "Invalid address family ({family})"),
             InvalidDirection(direction) => write!(f, "Invalid direction ({direction})"),
-            InvalidTransportProtocol(protocol) => {
-                write!(f, "Invalid transport protocol ({protocol})")
-            }
             StateAlreadyActive => write!(f, "Target state is already active"),
             AnchorDoesNotExist => write!(f, "Anchor does not exist"),
+            RuleDoesNotExist => write!(f, "Rule does not exist"),
+            InvalidRulePosition => write!(f, "Rule number of the rule position is out of range"),
+            UnsupportedRule(msg) => write!(f, "Unsupported rule: {msg}"),
             Ioctl(_) => write!(f, "Error during ioctl syscall"),
+            RuleAtIndex(index, _) => write!(f, "Failed to add rule at index {index}"),
         }
     }
 }
@@ -206,6 +282,9 @@ impl std::error::Error for Error {
         match &self.0 {
             DeviceOpen(_, e) => Some(e),
             Ioctl(e) => Some(e),
+            NetListRead(e) => Some(e),
+            ResolveHost(_, e) => Some(e),
+            RuleAtIndex(_, e) => Some(e),
             _ => None,
         }
     }
@@ -262,6 +341,17 @@ fn compare_cstr_safe(s: &str, c_str: &[std::os::raw::c_char]) -> bool {
     s.as_bytes() == c_str.to_bytes()
 }
 
+/// Internal function to safely read a Rust string out of a raw C string slice. Returns an error
+/// if the slice is not null terminated or does not contain valid UTF-8.
+fn cstr_to_string(c_str: &[std::os::raw::c_char]) -> std::result::Result<String, &'static str> {
+    let c_str: &[u8] = zerocopy::transmute_ref!(c_str);
+    CStr::from_bytes_until_nul(c_str)
+        .map_err(|_| "Missing null byte")?
+        .to_str()
+        .map(str::to_owned)
+        .map_err(|_| "Invalid UTF-8")
+}
+
 /// Struct communicating with the PF firewall.
 pub struct PfCtl {
     file: File,
@@ -336,19 +426,169 @@ impl PfCtl {
         )
     }
 
-    // TODO(linus): Make more generic. No hardcoded ADD_TAIL etc.
+    /// Returns the anchors directly under `parent`, which is given as a full anchor path. An
+    /// empty `parent` lists the anchors in the main ruleset.
+    pub fn list_anchors(&mut self, parent: &str) -> Result<Vec<AnchorInfo>> {
+        let mut pfioc_ruleset = ffi::pfvar::pfioc_ruleset::new_zeroed();
+        utils::copy_anchor_name(parent, &mut pfioc_ruleset.path[..])?;
+        ioctl_guard!(ffi::pf_get_rulesets(self.fd(), &mut pfioc_ruleset))?;
+
+        (0..pfioc_ruleset.nr)
+            .map(|i| {
+                pfioc_ruleset.nr = i;
+                ioctl_guard!(ffi::pf_get_ruleset(self.fd(), &mut pfioc_ruleset))?;
+                let name = cstr_to_string(&pfioc_ruleset.name)
+                    .map_err(ErrorInternal::InvalidAnchorName)?;
+                let path = if parent.is_empty() {
+                    name
+                } else {
+                    format!("{parent}/{name}")
+                };
+                Ok(AnchorInfo {
+                    filter_rules: self.get_rule_count(&path, AnchorKind::Filter)?,
+                    nat_rules: self.get_rule_count(&path, AnchorKind::Nat)?,
+                    redirect_rules: self.get_rule_count(&path, AnchorKind::Redirect)?,
+                    scrub_rules: self.get_rule_count(&path, AnchorKind::Scrub)?,
+                    path,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns all anchors in PF as trees, one for each anchor in the main ruleset.
+    pub fn anchor_tree(&mut self) -> Result<Vec<AnchorTree>> {
+        self.get_anchor_trees("")
+    }
+
+    /// Returns every rule of the given kind in the main ruleset that calls into an anchor, in the
+    /// order PF evaluates them.
+    pub fn list_anchor_hooks(&mut self, kind: AnchorKind) -> Result<Vec<AnchorHook>> {
+        self.get_raw_rules("", kind)?
+            .into_iter()
+            .filter(|pfioc_rule| pfioc_rule.anchor_call[0] != 0)
+            .map(AnchorHook::try_from)
+            .collect()
+    }
+
+    /// Appends a rule of any kind to the given anchor.
+    pub fn add(&mut self, anchor: &str, rule: &Rule) -> Result<()> {
+        self.insert(anchor, rule, RulePosition::Tail)
+    }
+
+    /// Inserts a rule of any kind into the given anchor at the given position.
+    pub fn insert(&mut self, anchor: &str, rule: &Rule, position: RulePosition) -> Result<()> {
+        match rule {
+            Rule::Filter(rule) => self.insert_rule(anchor, rule, position),
+            Rule::Nat(rule) => self.insert_nat_rule(anchor, rule, position),
+            Rule::Redirect(rule) => self.insert_redirect_rule(anchor, rule, position),
+            Rule::Scrub(rule) => self.insert_scrub_rule(anchor, rule, position),
+        }
+    }
+
+    /// Appends a filter rule to the given anchor. Rules with lists are expanded into several
+    /// rules first, see `FilterRule::expand`.
     pub fn add_rule(&mut self, anchor: &str, rule: &FilterRule) -> Result<()> {
+        self.insert_rule(anchor, rule, RulePosition::Tail)
+    }
+
+    /// Inserts a filter rule into the given anchor at the given position. Rules with lists are
+    /// expanded into several rules, which are inserted in order at the position.
+    ///
+    /// Returns `ErrorKind::InvalidRulePosition` if the numbers the expanded rules are inserted at
+    /// do not fit in a `u32`.
+    pub fn insert_rule(
+        &mut self,
+        anchor: &str,
+        rule: &FilterRule,
+        position: RulePosition,
+    ) -> Result<()> {
+        let pool_ticket = utils::get_pool_ticket(self.fd())?;
+        let ticket = utils::get_ticket(self.fd(), anchor, AnchorKind::Filter)?;
+        self.change_expanded_filter_rule(anchor, rule, position, pool_ticket, ticket)
+            .map(|_| ())
+    }
+
+    /// Appends all the given filter rules to the given anchor, in order.
+    ///
+    /// The tickets needed to change the anchor are only fetched once for the whole batch, making
+    /// this a lot faster than calling `add_rule` for each rule. If a rule fails, the rules before
+    /// it stay in the anchor and the returned error has the index of the failing rule in
+    /// `Error::rule_index`.
+    pub fn add_rules<'a>(
+        &mut self,
+        anchor: &str,
+        rules: impl IntoIterator<Item = &'a FilterRule>,
+    ) -> Result<()> {
+        self.add_rules_with(
+            anchor,
+            AnchorKind::Filter,
+            rules,
+            Self::change_expanded_filter_rule,
+        )
+    }
+
+    /// Replaces the filter rule with number `nr` in the given anchor.
+    ///
+    /// This is done by inserting the new rule after the old one and then removing the old one, so
+    /// for a short moment both rules are active.
+    pub fn replace_rule_at(&mut self, anchor: &str, rule: &FilterRule, nr: u32) -> Result<()> {
+        self.insert_rule(anchor, rule, RulePosition::After(nr))?;
+        self.remove_rule_at(anchor, AnchorKind::Filter, nr)
+    }
+
+    /// Removes the rule with number `nr` of the given kind from the given anchor. Rule numbers are
+    /// the positions of the rules in the anchor, in the order PF evaluates them, starting at 0.
+    pub fn remove_rule_at(&mut self, anchor: &str, kind: AnchorKind, nr: u32) -> Result<()> {
         let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
 
-        pfioc_rule.pool_ticket = utils::get_pool_ticket(self.fd())?;
-        pfioc_rule.ticket = utils::get_ticket(self.fd(), anchor, AnchorKind::Filter)?;
+        pfioc_rule.ticket = utils::get_ticket(self.fd(), anchor, kind)?;
         utils::copy_anchor_name(anchor, &mut pfioc_rule.anchor[..])?;
-        rule.try_copy_to(&mut pfioc_rule.rule)?;
+        pfioc_rule.rule.action = kind.into();
 
-        pfioc_rule.action = ffi::pfvar::PF_CHANGE_ADD_TAIL as u32;
+        pfioc_rule.action = ffi::pfvar::PF_CHANGE_REMOVE as u32;
+        pfioc_rule.nr = nr;
         ioctl_guard!(ffi::pf_change_rule(self.fd(), &mut pfioc_rule))
     }
 
+    /// Removes the filter rule in the given anchor that is equal to `rule`, or all such rules if
+    /// `mode` is `RemoveMode::All`. Returns the number of removed rules.
+    ///
+    /// A rule with lists is compared as the rules `FilterRule::expand` gives. With
+    /// `RemoveMode::First` the first match of every expanded rule is removed.
+    ///
+    /// Returns `ErrorKind::RuleDoesNotExist` if no rule in the anchor matches, or with
+    /// `RemoveMode::First`, if any of the expanded rules has no match. Nothing is removed then.
+    pub fn remove_matching_rule(
+        &mut self,
+        anchor: &str,
+        rule: &FilterRule,
+        mode: RemoveMode,
+    ) -> Result<u32> {
+        let expected = rule
+            .expand()?
+            .iter()
+            .map(|rule| rule.normalized())
+            .collect::<Result<Vec<_>>>()?;
+        self.remove_rules_where(
+            anchor,
+            AnchorKind::Filter,
+            mode,
+            &expected,
+            |pf, pfioc_rule| pf.decode_filter_rule(pfioc_rule),
+        )
+    }
+
+    /// Returns the filter rules in the given anchor, in the order PF evaluates them.
+    ///
+    /// Anchor rules, calling into nested anchors, are not included.
+    pub fn get_filter_rules(&mut self, anchor: &str) -> Result<Vec<FilterRule>> {
+        self.get_raw_rules(anchor, AnchorKind::Filter)?
+            .iter()
+            .filter(|pfioc_rule| pfioc_rule.anchor_call[0] == 0)
+            .map(|pfioc_rule| self.decode_filter_rule(pfioc_rule))
+            .collect()
+    }
+
     pub fn set_rules(&mut self, anchor: &str, change: AnchorChange) -> Result<()> {
         let mut trans = Transaction::new();
         trans.add_change(anchor, change);
@@ -356,13 +596,49 @@ impl PfCtl {
     }
 
     pub fn add_nat_rule(&mut self, anchor: &str, rule: &NatRule) -> Result<()> {
+        self.insert_nat_rule(anchor, rule, RulePosition::Tail)
+    }
+
+    /// Inserts a NAT rule into the given anchor at the given position.
+    pub fn insert_nat_rule(
+        &mut self,
+        anchor: &str,
+        rule: &NatRule,
+        position: RulePosition,
+    ) -> Result<()> {
+        let pool_ticket = utils::get_pool_ticket(self.fd())?;
+        let ticket = utils::get_ticket(self.fd(), anchor, AnchorKind::Nat)?;
+        self.change_nat_rule(anchor, rule, position, pool_ticket, ticket)
+    }
+
+    /// Appends all the given NAT rules to the given anchor, in order. See `add_rules`.
+    pub fn add_nat_rules<'a>(
+        &mut self,
+        anchor: &str,
+        rules: impl IntoIterator<Item = &'a NatRule>,
+    ) -> Result<()> {
+        self.add_rules_with(
+            anchor,
+            AnchorKind::Nat,
+            rules,
+            single_rule(Self::change_nat_rule),
+        )
+    }
+
+    /// Sends a single DIOCCHANGERULE for a NAT rule, using the given tickets.
+    fn change_nat_rule(
+        &self,
+        anchor: &str,
+        rule: &NatRule,
+        position: RulePosition,
+        pool_ticket: u32,
+        ticket: u32,
+    ) -> Result<()> {
         // prepare pfioc_rule
         let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
         utils::copy_anchor_name(anchor, &mut pfioc_rule.anchor[..])?;
         rule.try_copy_to(&mut pfioc_rule.rule)?;
 
-        let pool_ticket = utils::get_pool_ticket(self.fd())?;
-
         if let Some(nat_to) = rule.get_nat_to() {
             // register NAT address in newly created address pool
             utils::add_pool_address(self.fd(), nat_to.ip(), pool_ticket)?;
@@ -375,22 +651,95 @@ impl PfCtl {
 
         // set tickets
         pfioc_rule.pool_ticket = pool_ticket;
-        pfioc_rule.ticket = utils::get_ticket(self.fd(), anchor, AnchorKind::Nat)?;
+        pfioc_rule.ticket = ticket;
 
-        // append rule
-        pfioc_rule.action = ffi::pfvar::PF_CHANGE_ADD_TAIL as u32;
+        // insert rule
+        position.copy_to(&mut pfioc_rule);
         ioctl_guard!(ffi::pf_change_rule(self.fd(), &mut pfioc_rule))
     }
 
+    /// Replaces the NAT rule with number `nr` in the given anchor. See `replace_rule_at`.
+    pub fn replace_nat_rule_at(&mut self, anchor: &str, rule: &NatRule, nr: u32) -> Result<()> {
+        self.insert_nat_rule(anchor, rule, RulePosition::After(nr))?;
+        self.remove_rule_at(anchor, AnchorKind::Nat, nr)
+    }
+
+    /// Removes the NAT rule in the given anchor that is equal to `rule`. See
+    /// `remove_matching_rule`.
+    pub fn remove_matching_nat_rule(
+        &mut self,
+        anchor: &str,
+        rule: &NatRule,
+        mode: RemoveMode,
+    ) -> Result<u32> {
+        let expected = rule.normalized()?;
+        self.remove_rules_where(
+            anchor,
+            AnchorKind::Nat,
+            mode,
+            &[expected],
+            |pf, pfioc_rule| pf.decode_nat_rule(pfioc_rule),
+        )
+    }
+
+    /// Returns the NAT rules in the given anchor, in the order PF evaluates them, with the
+    /// addresses they translate to read back from their address pools.
+    ///
+    /// Anchor rules, calling into nested anchors, are not included.
+    pub fn get_nat_rules(&mut self, anchor: &str) -> Result<Vec<NatRule>> {
+        self.get_raw_rules(anchor, AnchorKind::Nat)?
+            .iter()
+            .filter(|pfioc_rule| pfioc_rule.anchor_call[0] == 0)
+            .map(|pfioc_rule| self.decode_nat_rule(pfioc_rule))
+            .collect()
+    }
+
     pub fn add_redirect_rule(&mut self, anchor: &str, rule: &RedirectRule) -> Result<()> {
+        self.insert_redirect_rule(anchor, rule, RulePosition::Tail)
+    }
+
+    /// Inserts a redirect rule into the given anchor at the given position.
+    pub fn insert_redirect_rule(
+        &mut self,
+        anchor: &str,
+        rule: &RedirectRule,
+        position: RulePosition,
+    ) -> Result<()> {
+        let pool_ticket = utils::get_pool_ticket(self.fd())?;
+        let ticket = utils::get_ticket(self.fd(), anchor, AnchorKind::Redirect)?;
+        self.change_redirect_rule(anchor, rule, position, pool_ticket, ticket)
+    }
+
+    /// Appends all the given redirect rules to the given anchor, in order. See `add_rules`.
+    pub fn add_redirect_rules<'a>(
+        &mut self,
+        anchor: &str,
+        rules: impl IntoIterator<Item = &'a RedirectRule>,
+    ) -> Result<()> {
+        self.add_rules_with(
+            anchor,
+            AnchorKind::Redirect,
+            rules,
+            single_rule(Self::change_redirect_rule),
+        )
+    }
+
+    /// Sends a single DIOCCHANGERULE for a redirect rule, using the given tickets.
+    fn change_redirect_rule(
+        &self,
+        anchor: &str,
+        rule: &RedirectRule,
+        position: RulePosition,
+        pool_ticket: u32,
+        ticket: u32,
+    ) -> Result<()> {
         // prepare pfioc_rule
         let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
         utils::copy_anchor_name(anchor, &mut pfioc_rule.anchor[..])?;
         rule.try_copy_to(&mut pfioc_rule.rule)?;
 
-        // register redirect address in newly created address pool
+        // register redirect address in the address pool
         let redirect_to = rule.get_redirect_to();
-        let pool_ticket = utils::get_pool_ticket(self.fd())?;
         utils::add_pool_address(self.fd(), redirect_to.ip(), pool_ticket)?;
 
         // copy address pool in pf_rule
@@ -400,25 +749,150 @@ impl PfCtl {
 
         // set tickets
         pfioc_rule.pool_ticket = pool_ticket;
-        pfioc_rule.ticket = utils::get_ticket(self.fd(), anchor, AnchorKind::Redirect)?;
+        pfioc_rule.ticket = ticket;
 
-        // append rule
-        pfioc_rule.action = ffi::pfvar::PF_CHANGE_ADD_TAIL as u32;
+        // insert rule
+        position.copy_to(&mut pfioc_rule);
         ioctl_guard!(ffi::pf_change_rule(self.fd(), &mut pfioc_rule))
     }
 
+    /// Replaces the redirect rule with number `nr` in the given anchor. See `replace_rule_at`.
+    pub fn replace_redirect_rule_at(
+        &mut self,
+        anchor: &str,
+        rule: &RedirectRule,
+        nr: u32,
+    ) -> Result<()> {
+        self.insert_redirect_rule(anchor, rule, RulePosition::After(nr))?;
+        self.remove_rule_at(anchor, AnchorKind::Redirect, nr)
+    }
+
+    /// Removes the redirect rule in the given anchor that is equal to `rule`. See
+    /// `remove_matching_rule`.
+    pub fn remove_matching_redirect_rule(
+        &mut self,
+        anchor: &str,
+        rule: &RedirectRule,
+        mode: RemoveMode,
+    ) -> Result<u32> {
+        let expected = rule.normalized()?;
+        self.remove_rules_where(
+            anchor,
+            AnchorKind::Redirect,
+            mode,
+            &[expected],
+            |pf, pfioc_rule| pf.decode_redirect_rule(pfioc_rule),
+        )
+    }
+
+    /// Returns the redirect rules in the given anchor, in the order PF evaluates them, with the
+    /// addresses they redirect to read back from their address pools.
+    ///
+    /// Anchor rules, calling into nested anchors, are not included.
+    pub fn get_redirect_rules(&mut self, anchor: &str) -> Result<Vec<RedirectRule>> {
+        self.get_raw_rules(anchor, AnchorKind::Redirect)?
+            .iter()
+            .filter(|pfioc_rule| pfioc_rule.anchor_call[0] == 0)
+            .map(|pfioc_rule| self.decode_redirect_rule(pfioc_rule))
+            .collect()
+    }
+
+    /// Returns the counters of every rule of the given kind in the given anchor, in the order PF
+    /// evaluates the rules. Anchor rules, calling into nested anchors, are included.
+    pub fn get_rule_stats(&mut self, anchor: &str, kind: AnchorKind) -> Result<Vec<RuleStats>> {
+        self.get_raw_rules(anchor, kind)?
+            .into_iter()
+            .map(|pfioc_rule| RuleStats::try_from(pfioc_rule.rule))
+            .collect()
+    }
+
+    /// Same as `get_rule_stats`, but also zeroes the evaluation, packet and byte counters of
+    /// each rule as it is read, so no matches are lost between reading and clearing.
+    pub fn get_and_clear_rule_stats(
+        &mut self,
+        anchor: &str,
+        kind: AnchorKind,
+    ) -> Result<Vec<RuleStats>> {
+        self.get_raw_rules_with_action(anchor, kind, ffi::pfvar::PF_GET_CLR_CNTR as u32)?
+            .into_iter()
+            .map(|pfioc_rule| RuleStats::try_from(pfioc_rule.rule))
+            .collect()
+    }
+
     pub fn add_scrub_rule(&mut self, anchor: &str, rule: &ScrubRule) -> Result<()> {
+        self.insert_scrub_rule(anchor, rule, RulePosition::Tail)
+    }
+
+    /// Inserts a scrub rule into the given anchor at the given position.
+    pub fn insert_scrub_rule(
+        &mut self,
+        anchor: &str,
+        rule: &ScrubRule,
+        position: RulePosition,
+    ) -> Result<()> {
+        let pool_ticket = utils::get_pool_ticket(self.fd())?;
+        let ticket = utils::get_ticket(self.fd(), anchor, AnchorKind::Scrub)?;
+        self.change_scrub_rule(anchor, rule, position, pool_ticket, ticket)
+    }
+
+    /// Appends all the given scrub rules to the given anchor, in order. See `add_rules`.
+    pub fn add_scrub_rules<'a>(
+        &mut self,
+        anchor: &str,
+        rules: impl IntoIterator<Item = &'a ScrubRule>,
+    ) -> Result<()> {
+        self.add_rules_with(
+            anchor,
+            AnchorKind::Scrub,
+            rules,
+            single_rule(Self::change_scrub_rule),
+        )
+    }
+
+    /// Sends a single DIOCCHANGERULE for a scrub rule, using the given tickets.
+    fn change_scrub_rule(
+        &self,
+        anchor: &str,
+        rule: &ScrubRule,
+        position: RulePosition,
+        pool_ticket: u32,
+        ticket: u32,
+    ) -> Result<()> {
         let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
 
-        pfioc_rule.pool_ticket = utils::get_pool_ticket(self.fd())?;
-        pfioc_rule.ticket = utils::get_ticket(self.fd(), anchor, AnchorKind::Scrub)?;
+        pfioc_rule.pool_ticket = pool_ticket;
+        pfioc_rule.ticket = ticket;
         utils::copy_anchor_name(anchor, &mut pfioc_rule.anchor[..])?;
         rule.try_copy_to(&mut pfioc_rule.rule)?;
 
-        pfioc_rule.action = ffi::pfvar::PF_CHANGE_ADD_TAIL as u32;
+        position.copy_to(&mut pfioc_rule);
         ioctl_guard!(ffi::pf_change_rule(self.fd(), &mut pfioc_rule))
     }
 
+    /// Replaces the scrub rule with number `nr` in the given anchor. See `replace_rule_at`.
+    pub fn replace_scrub_rule_at(&mut self, anchor: &str, rule: &ScrubRule, nr: u32) -> Result<()> {
+        self.insert_scrub_rule(anchor, rule, RulePosition::After(nr))?;
+        self.remove_rule_at(anchor, AnchorKind::Scrub, nr)
+    }
+
+    /// Removes the scrub rule in the given anchor that is equal to `rule`. See
+    /// `remove_matching_rule`.
+    pub fn remove_matching_scrub_rule(
+        &mut self,
+        anchor: &str,
+        rule: &ScrubRule,
+        mode: RemoveMode,
+    ) -> Result<u32> {
+        let expected = rule.normalized()?;
+        self.remove_rules_where(
+            anchor,
+            AnchorKind::Scrub,
+            mode,
+            &[expected],
+            |_, pfioc_rule| ScrubRule::try_from(pfioc_rule.rule),
+        )
+    }
+
     pub fn flush_rules(&mut self, anchor: &str, kind: RulesetKind) -> Result<()> {
         let mut trans = Transaction::new();
         let mut anchor_change = AnchorChange::new();
@@ -432,6 +906,178 @@ impl PfCtl {
         trans.commit()
     }
 
+    /// Creates a table with the given name and flags in `anchor`. Returns `true` if the table
+    /// was created and `false` if it already existed.
+    ///
+    /// PF destroys tables that are not referenced by any rule, so a table meant to exist on its
+    /// own must be created with `TableFlags::PERSIST`. Only `PERSIST` and `CONST` can be set, any
+    /// other flag gives an `InvalidTableFlags` error.
+    pub fn create_table(&mut self, anchor: &str, name: &str, flags: TableFlags) -> Result<bool> {
+        flags.validate_settable()?;
+        let mut table = setup_pfr_table(anchor, name)?;
+        table.pfrt_flags = flags.bits();
+        let mut pfioc_table = setup_pfioc_table(&mut table);
+        ioctl_guard!(ffi::pf_add_tables(self.fd(), &mut pfioc_table))?;
+        Ok(pfioc_table.pfrio_nadd > 0)
+    }
+
+    /// Deletes the table with the given name from `anchor`, together with all its addresses.
+    /// Returns `true` if the table was deleted and `false` if it did not exist.
+    pub fn delete_table(&mut self, anchor: &str, name: &str) -> Result<bool> {
+        let mut table = setup_pfr_table(anchor, name)?;
+        let mut pfioc_table = setup_pfioc_table(&mut table);
+        ioctl_guard!(ffi::pf_delete_tables(self.fd(), &mut pfioc_table))?;
+        Ok(pfioc_table.pfrio_ndel > 0)
+    }
+
+    /// Returns all tables defined directly in `anchor`. An empty `anchor` lists the tables in
+    /// the main ruleset.
+    pub fn list_tables(&mut self, anchor: &str) -> Result<Vec<TableInfo>> {
+        let mut pfioc_table = ffi::pfvar::pfioc_table::new_zeroed();
+        utils::copy_anchor_name(anchor, &mut pfioc_table.pfrio_table.pfrt_anchor[..])?;
+        self.get_table_entries::<ffi::pfvar::pfr_tstats>(pfioc_table, ffi::pf_get_table_stats)?
+            .into_iter()
+            .map(TableInfo::try_from)
+            .collect()
+    }
+
+    /// Adds `addrs` to the table `name` in `anchor`. Returns the number of addresses that were
+    /// added, addresses already in the table are not counted.
+    pub fn table_add_addrs<A: Into<TableAddr>>(
+        &mut self,
+        anchor: &str,
+        name: &str,
+        addrs: impl IntoIterator<Item = A>,
+    ) -> Result<u32> {
+        let mut buf = to_pfr_addrs(addrs);
+        let mut pfioc_table = setup_pfioc_table_addrs(anchor, name, &mut buf)?;
+        pfioc_table.pfrio_flags = ffi::pfvar::PFR_FLAG_ATOMIC as i32;
+        ioctl_guard!(ffi::pf_add_table_addrs(self.fd(), &mut pfioc_table))?;
+        Ok(pfioc_table.pfrio_nadd as u32)
+    }
+
+    /// Deletes `addrs` from the table `name` in `anchor`. Returns the number of addresses that
+    /// were deleted, addresses not in the table are not counted.
+    pub fn table_delete_addrs<A: Into<TableAddr>>(
+        &mut self,
+        anchor: &str,
+        name: &str,
+        addrs: impl IntoIterator<Item = A>,
+    ) -> Result<u32> {
+        let mut buf = to_pfr_addrs(addrs);
+        let mut pfioc_table = setup_pfioc_table_addrs(anchor, name, &mut buf)?;
+        pfioc_table.pfrio_flags = ffi::pfvar::PFR_FLAG_ATOMIC as i32;
+        ioctl_guard!(ffi::pf_delete_table_addrs(self.fd(), &mut pfioc_table))?;
+        Ok(pfioc_table.pfrio_ndel as u32)
+    }
+
+    /// Replaces the addresses of the table `name` in `anchor` with `addrs`. The replacement is
+    /// done atomically by PF, so the table never appears empty or partially updated to packets
+    /// being filtered.
+    pub fn table_replace_addrs<A: Into<TableAddr>>(
+        &mut self,
+        anchor: &str,
+        name: &str,
+        addrs: impl IntoIterator<Item = A>,
+    ) -> Result<TableChanges> {
+        let mut buf = to_pfr_addrs(addrs);
+        let mut pfioc_table = setup_pfioc_table_addrs(anchor, name, &mut buf)?;
+        pfioc_table.pfrio_flags = ffi::pfvar::PFR_FLAG_ATOMIC as i32;
+        ioctl_guard!(ffi::pf_set_table_addrs(self.fd(), &mut pfioc_table))?;
+        Ok(TableChanges {
+            added: pfioc_table.pfrio_nadd as u32,
+            deleted: pfioc_table.pfrio_ndel as u32,
+            changed: pfioc_table.pfrio_nchange as u32,
+        })
+    }
+
+    /// Removes all addresses from the table `name` in `anchor`. Returns the number of addresses
+    /// that were removed.
+    pub fn table_clear(&mut self, anchor: &str, name: &str) -> Result<u32> {
+        let mut pfioc_table = setup_pfioc_table_addrs(anchor, name, &mut [])?;
+        pfioc_table.pfrio_flags = ffi::pfvar::PFR_FLAG_ATOMIC as i32;
+        ioctl_guard!(ffi::pf_clear_table_addrs(self.fd(), &mut pfioc_table))?;
+        Ok(pfioc_table.pfrio_ndel as u32)
+    }
+
+    /// Returns all addresses in the table `name` in `anchor`.
+    pub fn table_get_addrs(&mut self, anchor: &str, name: &str) -> Result<Vec<TableAddr>> {
+        let pfioc_table = setup_pfioc_table_addrs(anchor, name, &mut [])?;
+        self.get_table_entries::<ffi::pfvar::pfr_addr>(pfioc_table, ffi::pf_get_table_addrs)?
+            .into_iter()
+            .map(TableAddr::try_from)
+            .collect()
+    }
+
+    /// Tests which of `addrs` the table `name` in `anchor` matches. An address is matched if the
+    /// most specific table entry containing it is not negated. Returns every address together
+    /// with whether it is matched, in the order given.
+    pub fn table_test(
+        &mut self,
+        anchor: &str,
+        name: &str,
+        addrs: &[IpAddr],
+    ) -> Result<Vec<(IpAddr, bool)>> {
+        let mut buf = to_pfr_addrs(addrs.iter().copied());
+        let mut pfioc_table = setup_pfioc_table_addrs(anchor, name, &mut buf)?;
+        ioctl_guard!(ffi::pf_test_table_addrs(self.fd(), &mut pfioc_table))?;
+        let matched = ffi::pfvar::PFR_FB_MATCH as u8;
+        Ok(addrs
+            .iter()
+            .zip(&buf)
+            .map(|(addr, pfr_addr)| (*addr, pfr_addr.pfra_fback == matched))
+            .collect())
+    }
+
+    /// Returns the counters of every address in the table `name` in `anchor`.
+    pub fn table_get_addr_stats(
+        &mut self,
+        anchor: &str,
+        name: &str,
+    ) -> Result<Vec<TableAddrStats>> {
+        let pfioc_table = setup_pfioc_table_addrs(anchor, name, &mut [])?;
+        self.get_table_entries::<ffi::pfvar::pfr_astats>(pfioc_table, ffi::pf_get_table_addr_stats)?
+            .into_iter()
+            .map(TableAddrStats::try_from)
+            .collect()
+    }
+
+    /// Zeroes the counters of `addrs` in the table `name` in `anchor`. Returns the number of
+    /// addresses whose counters were cleared, addresses not in the table are not counted.
+    pub fn table_clear_addr_stats<A: Into<TableAddr>>(
+        &mut self,
+        anchor: &str,
+        name: &str,
+        addrs: impl IntoIterator<Item = A>,
+    ) -> Result<u32> {
+        let mut buf = to_pfr_addrs(addrs);
+        let mut pfioc_table = setup_pfioc_table_addrs(anchor, name, &mut buf)?;
+        pfioc_table.pfrio_flags = ffi::pfvar::PFR_FLAG_ATOMIC as i32;
+        ioctl_guard!(ffi::pf_clear_table_addr_stats(self.fd(), &mut pfioc_table))?;
+        // pfrio_nzero is an alias of pfrio_nadd
+        Ok(pfioc_table.pfrio_nadd as u32)
+    }
+
+    /// Deletes the addresses of the table `name` in `anchor` whose counters were last cleared
+    /// more than `older_than` ago, like `pfctl -T expire`. Addresses that were never cleared
+    /// count from the time they were added. Returns the number of deleted addresses.
+    pub fn table_expire(&mut self, anchor: &str, name: &str, older_than: Duration) -> Result<u32> {
+        let Some(limit) = SystemTime::now().checked_sub(older_than) else {
+            return Ok(0);
+        };
+        let expired: Vec<TableAddr> = self
+            .table_get_addr_stats(anchor, name)?
+            .into_iter()
+            .filter(|stats| stats.zeroed_at() != SystemTime::UNIX_EPOCH)
+            .filter(|stats| stats.zeroed_at() < limit)
+            .map(|stats| stats.addr())
+            .collect();
+        if expired.is_empty() {
+            return Ok(0);
+        }
+        self.table_delete_addrs(anchor, name, expired)
+    }
+
     /// Clear states created by rules in anchor.
     /// Returns total number of removed states upon success, otherwise
     /// ErrorKind::AnchorDoesNotExist if anchor does not exist.
@@ -601,6 +1247,230 @@ impl PfCtl {
         Err(Error::from(ErrorInternal::AnchorDoesNotExist))
     }
 
+    /// Helper function to read all rules of the given kind in an anchor, including anchor rules.
+    ///
+    /// Each returned `pfioc_rule` holds the rule number in `nr` and the ticket of the ruleset in
+    /// `ticket`, so it can be used for further requests on the same rule.
+    fn get_raw_rules(&self, anchor: &str, kind: AnchorKind) -> Result<Vec<ffi::pfvar::pfioc_rule>> {
+        self.get_raw_rules_with_action(anchor, kind, ffi::pfvar::PF_GET_NONE as u32)
+    }
+
+    /// Same as `get_raw_rules`, but passes `get_action` to every DIOCGETRULE call. With
+    /// `PF_GET_CLR_CNTR` the kernel zeroes the counters of each rule after copying it out.
+    fn get_raw_rules_with_action(
+        &self,
+        anchor: &str,
+        kind: AnchorKind,
+        get_action: u32,
+    ) -> Result<Vec<ffi::pfvar::pfioc_rule>> {
+        let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
+        pfioc_rule.rule.action = kind.into();
+        utils::copy_anchor_name(anchor, &mut pfioc_rule.anchor[..])?;
+        ioctl_guard!(ffi::pf_get_rules(self.fd(), &mut pfioc_rule))?;
+        pfioc_rule.action = get_action;
+        (0..pfioc_rule.nr)
+            .map(|i| {
+                pfioc_rule.nr = i;
+                ioctl_guard!(ffi::pf_get_rule(self.fd(), &mut pfioc_rule))?;
+                Ok(pfioc_rule)
+            })
+            .collect()
+    }
+
+    /// Sends a single DIOCCHANGERULE for a filter rule, using the given tickets.
+    fn change_filter_rule(
+        &self,
+        anchor: &str,
+        rule: &FilterRule,
+        position: RulePosition,
+        pool_ticket: u32,
+        ticket: u32,
+    ) -> Result<()> {
+        let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
+
+        pfioc_rule.pool_ticket = pool_ticket;
+        pfioc_rule.ticket = ticket;
+        utils::copy_anchor_name(anchor, &mut pfioc_rule.anchor[..])?;
+        rule.try_copy_to(&mut pfioc_rule.rule)?;
+
+        position.copy_to(&mut pfioc_rule);
+        ioctl_guard!(ffi::pf_change_rule(self.fd(), &mut pfioc_rule))
+    }
+
+    /// Expands a filter rule with `FilterRule::expand` and inserts the resulting rules in order
+    /// at `position` with `change_filter_rule`. Returns the number of inserted rules.
+    fn change_expanded_filter_rule(
+        &self,
+        anchor: &str,
+        rule: &FilterRule,
+        position: RulePosition,
+        pool_ticket: u32,
+        ticket: u32,
+    ) -> Result<u32> {
+        let expanded = rule.expand()?;
+        for (offset, rule) in (0..).zip(&expanded) {
+            let position = position
+                .offset_by(offset)
+                .ok_or_else(|| Error::from(ErrorInternal::InvalidRulePosition))?;
+            self.change_filter_rule(
+                anchor,
+                rule,
+                position,
+                pool_ticket,
+                ticket.wrapping_add(offset),
+            )?;
+        }
+        Ok(expanded.len() as u32)
+    }
+
+    /// Appends rules to an anchor with `change_rule`, fetching the pool and ruleset tickets only
+    /// once. The pool ticket stays valid since every added rule empties the address pool buffer
+    /// again, and PF increments the ruleset ticket by one for every successful change.
+    /// `change_rule` returns the number of rules it added.
+    fn add_rules_with<'a, R: 'a, F>(
+        &mut self,
+        anchor: &str,
+        kind: AnchorKind,
+        rules: impl IntoIterator<Item = &'a R>,
+        change_rule: F,
+    ) -> Result<()>
+    where
+        F: Fn(&Self, &str, &R, RulePosition, u32, u32) -> Result<u32>,
+    {
+        let pool_ticket = utils::get_pool_ticket(self.fd())?;
+        let mut ticket = utils::get_ticket(self.fd(), anchor, kind)?;
+        for (index, rule) in rules.into_iter().enumerate() {
+            let added = change_rule(self, anchor, rule, RulePosition::Tail, pool_ticket, ticket)
+                .map_err(|e| Error::from(ErrorInternal::RuleAtIndex(index, Box::new(e))))?;
+            ticket = ticket.wrapping_add(added);
+        }
+        Ok(())
+    }
+
+    /// Removes the rules of the given kind in the given anchor that `decode` to one of
+    /// `expected`. With `RemoveMode::First` only the first match of every expected rule is
+    /// removed, and nothing is removed unless all of them match. Anchor rules are never removed.
+    fn remove_rules_where<R, F>(
+        &mut self,
+        anchor: &str,
+        kind: AnchorKind,
+        mode: RemoveMode,
+        expected: &[R],
+        decode: F,
+    ) -> Result<u32>
+    where
+        R: PartialEq,
+        F: Fn(&Self, &ffi::pfvar::pfioc_rule) -> Result<R>,
+    {
+        let mut found = vec![false; expected.len()];
+        let mut matching_nrs = Vec::new();
+        for pfioc_rule in self.get_raw_rules(anchor, kind)? {
+            if pfioc_rule.anchor_call[0] != 0 {
+                continue;
+            }
+            let Ok(rule) = decode(self, &pfioc_rule) else {
+                continue;
+            };
+            let matches = match mode {
+                RemoveMode::First => expected
+                    .iter()
+                    .zip(&mut found)
+                    .find(|(expected, found)| !**found && **expected == rule)
+                    .map(|(_, found)| *found = true)
+                    .is_some(),
+                RemoveMode::All => expected.contains(&rule),
+            };
+            if matches {
+                matching_nrs.push(pfioc_rule.nr);
+            }
+        }
+        let all_found = match mode {
+            RemoveMode::First => found.iter().all(|&found| found),
+            RemoveMode::All => !matching_nrs.is_empty(),
+        };
+        if !all_found {
+            return Err(Error::from(ErrorInternal::RuleDoesNotExist));
+        }
+
+        // Remove from the back so the numbers of the remaining matches stay valid
+        for &nr in matching_nrs.iter().rev() {
+            self.remove_rule_at(anchor, kind, nr)?;
+        }
+        Ok(matching_nrs.len() as u32)
+    }
+
+    /// Decodes a filter rule obtained from `get_raw_rules`, including its route pool address.
+    fn decode_filter_rule(&self, pfioc_rule: &ffi::pfvar::pfioc_rule) -> Result<FilterRule> {
+        let mut rule = FilterRule::try_from(pfioc_rule.rule)?;
+        if rule.get_route().get_pool_addr().is_some() {
+            let pool_addr = utils::get_pool_addrs(self.fd(), pfioc_rule)?
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| PoolAddr::from(Ip::Any));
+            rule.set_route_pool_addr(pool_addr);
+        }
+        Ok(rule)
+    }
+
+    /// Decodes a NAT rule obtained from `get_raw_rules`, including the address it translates to.
+    fn decode_nat_rule(&self, pfioc_rule: &ffi::pfvar::pfioc_rule) -> Result<NatRule> {
+        let mut rule = NatRule::try_from(pfioc_rule.rule)?;
+        if rule.get_nat_to().is_some() {
+            rule.set_nat_to_ip(self.get_pool_ip(pfioc_rule)?);
+        }
+        Ok(rule)
+    }
+
+    /// Decodes a redirect rule obtained from `get_raw_rules`, including the address it redirects
+    /// to.
+    fn decode_redirect_rule(&self, pfioc_rule: &ffi::pfvar::pfioc_rule) -> Result<RedirectRule> {
+        let mut rule = RedirectRule::try_from(pfioc_rule.rule)?;
+        rule.set_redirect_to_ip(self.get_pool_ip(pfioc_rule)?);
+        Ok(rule)
+    }
+
+    /// Returns the IP of the first address in the address pool of a rule obtained from
+    /// `get_raw_rules`, or `Ip::Any` if the pool is empty.
+    fn get_pool_ip(&self, pfioc_rule: &ffi::pfvar::pfioc_rule) -> Result<Ip> {
+        Ok(utils::get_pool_addrs(self.fd(), pfioc_rule)?
+            .first()
+            .map(PoolAddr::ip)
+            .unwrap_or(Ip::Any))
+    }
+
+    /// Helper function for the table ioctls that copy out a list of `T`, such as DIOCRGETADDRS.
+    /// With a too small buffer PF only reports back the number of entries, so the first call
+    /// asks for the size and the next ones retry in case entries were added in between.
+    fn get_table_entries<T: FromZeros>(
+        &self,
+        mut pfioc_table: ffi::pfvar::pfioc_table,
+        ioctl: unsafe fn(i32, *mut ffi::pfvar::pfioc_table) -> i32,
+    ) -> Result<Vec<T>> {
+        // Maximum number of ioctl retries before giving up
+        const MAX_RETRIES: usize = 3;
+
+        pfioc_table.pfrio_esize = mem::size_of::<T>() as i32;
+        let mut buf: Vec<T> = Vec::new();
+        let mut retry = 0;
+        loop {
+            pfioc_table.pfrio_buffer = buf.as_mut_ptr().cast::<c_void>();
+            pfioc_table.pfrio_size = buf.len() as i32;
+
+            ioctl_guard!(ioctl(self.fd(), &mut pfioc_table))?;
+            let num_entries = usize::try_from(pfioc_table.pfrio_size).unwrap_or_default();
+
+            retry += 1;
+            if retry < MAX_RETRIES && num_entries > buf.len() {
+                buf = T::new_vec_zeroed(num_entries).expect("allocation must succeed");
+            } else {
+                // truncate will only shorten the vec, but this is fine.
+                buf.truncate(num_entries);
+                break;
+            }
+        }
+        Ok(buf)
+    }
+
     /// Returns global number of states created by all stateful rules (see keep_state)
     fn get_num_states(&self) -> Result<u32> {
         let mut pfioc_states = ffi::pfvar::pfioc_states::new_zeroed();
@@ -610,12 +1480,86 @@ impl PfCtl {
         Ok(buffer_size / element_size)
     }
 
+    fn get_anchor_trees(&mut self, parent: &str) -> Result<Vec<AnchorTree>> {
+        self.list_anchors(parent)?
+            .into_iter()
+            .map(|anchor| {
+                let children = self.get_anchor_trees(anchor.path())?;
+                Ok(AnchorTree { anchor, children })
+            })
+            .collect()
+    }
+
+    /// Returns the number of rules of the given kind in the given anchor.
+    fn get_rule_count(&self, anchor: &str, kind: AnchorKind) -> Result<u32> {
+        let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
+        pfioc_rule.rule.action = kind.into();
+        utils::copy_anchor_name(anchor, &mut pfioc_rule.anchor[..])?;
+        ioctl_guard!(ffi::pf_get_rules(self.fd(), &mut pfioc_rule))?;
+        Ok(pfioc_rule.nr)
+    }
+
     /// Internal function for getting the raw file descriptor to PF.
     fn fd(&self) -> RawFd {
         self.file.as_raw_fd()
     }
 }
 
+/// Adapts a function sending a single DIOCCHANGERULE to `PfCtl::add_rules_with`.
+fn single_rule<R>(
+    change_rule: impl Fn(&PfCtl, &str, &R, RulePosition, u32, u32) -> Result<()>,
+) -> impl Fn(&PfCtl, &str, &R, RulePosition, u32, u32) -> Result<u32> {
+    move |pf, anchor, rule, position, pool_ticket, ticket| {
+        change_rule(pf, anchor, rule, position, pool_ticket, ticket).map(|()| 1)
+    }
+}
+
+/// Returns a `pfr_table` identifying the table `name` in `anchor`.
+fn setup_pfr_table(anchor: &str, name: &str) -> Result<ffi::pfvar::pfr_table> {
+    let mut table = ffi::pfvar::pfr_table::new_zeroed();
+    utils::copy_anchor_name(anchor, &mut table.pfrt_anchor[..])?;
+    utils::copy_table_name(name, &mut table.pfrt_name[..])?;
+    Ok(table)
+}
+
+/// Returns a `pfioc_table` for ioctls operating on the single table `table`.
+fn setup_pfioc_table(table: &mut ffi::pfvar::pfr_table) -> ffi::pfvar::pfioc_table {
+    let mut pfioc_table = ffi::pfvar::pfioc_table::new_zeroed();
+    pfioc_table.pfrio_buffer = (table as *mut ffi::pfvar::pfr_table).cast::<c_void>();
+    pfioc_table.pfrio_esize = mem::size_of::<ffi::pfvar::pfr_table>() as i32;
+    pfioc_table.pfrio_size = 1;
+    pfioc_table
+}
+
+/// Returns a `pfioc_table` for ioctls operating on the addresses `addrs` of the table `name` in
+/// `anchor`.
+fn setup_pfioc_table_addrs(
+    anchor: &str,
+    name: &str,
+    addrs: &mut [ffi::pfvar::pfr_addr],
+) -> Result<ffi::pfvar::pfioc_table> {
+    let mut pfioc_table = ffi::pfvar::pfioc_table::new_zeroed();
+    pfioc_table.pfrio_table = setup_pfr_table(anchor, name)?;
+    pfioc_table.pfrio_buffer = addrs.as_mut_ptr().cast::<c_void>();
+    pfioc_table.pfrio_esize = mem::size_of::<ffi::pfvar::pfr_addr>() as i32;
+    pfioc_table.pfrio_size = addrs.len() as i32;
+    Ok(pfioc_table)
+}
+
+/// Converts table addresses into their FFI representation.
+fn to_pfr_addrs<A: Into<TableAddr>>(
+    addrs: impl IntoIterator<Item = A>,
+) -> Vec<ffi::pfvar::pfr_addr> {
+    addrs
+        .into_iter()
+        .map(|addr| {
+            let mut pfr_addr = ffi::pfvar::pfr_addr::new_zeroed();
+            addr.into().copy_to(&mut pfr_addr);
+            pfr_addr
+        })
+        .collect()
+}
+
 /// Creates pfioc_states and returns a tuple of pfioc_states and vector of pfsync_state with the
 /// given number of elements.
 /// Since pfioc_states uses raw memory pointer to Vec<pfsync_state>, make sure that
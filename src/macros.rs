@@ -30,3 +30,59 @@ macro_rules! ioctl_guard {
         }
     };
 }
+
+/// Macro for implementing `as_str`, the string conversions, `Debug`, `Display` and `CopyTo<[i8]>`
+/// for a name type wrapping a `utils::FixedName`. The type must have a
+/// `new(&str) -> Result<Self>` constructor validating the name.
+macro_rules! impl_fixed_name {
+    ($name:ident) => {
+        impl $name {
+            /// Returns the name as a string slice.
+            pub fn as_str(&self) -> &str {
+                self.0.as_str()
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.as_str()
+            }
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = $crate::Error;
+
+            fn from_str(name: &str) -> $crate::Result<Self> {
+                $name::new(name)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = $crate::Error;
+
+            fn try_from(name: &str) -> $crate::Result<Self> {
+                $name::new(name)
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&self.as_str())
+                    .finish()
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(self.as_str(), f)
+            }
+        }
+
+        impl $crate::conversion::CopyTo<[i8]> for $name {
+            fn copy_to(&self, dst: &mut [i8]) {
+                $crate::conversion::CopyTo::copy_to(&self.0, dst)
+            }
+        }
+    };
+}
@@ -0,0 +1,265 @@
+// Copyright 2025 Mullvad VPN AB.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading lists of networks, such as block and allow lists, and reducing them to the smallest
+//! set of networks covering the same addresses.
+//!
+//! The result is meant to be loaded into a table, for example with
+//! `PfCtl::table_replace_addrs`, and referred to from rules with `Ip::Table`.
+//!
+//! ```
+//! let list = "
+//!     10.0.0.0/9     # private networks
+//!     10.128.0.0/9   # merged with the line above into 10.0.0.0/8
+//!     10.1.2.3
+//!     fd00::/8
+//! ";
+//! let networks = pfctl::netlist::parse_aggregated(list).unwrap();
+//! assert_eq!(
+//!     networks,
+//!     ["10.0.0.0/8".parse().unwrap(), "fd00::/8".parse().unwrap()]
+//! );
+//! ```
+
+use crate::{Error, ErrorInternal, Result};
+use ipnetwork::{IpNetwork, Ipv4Network, Ipv6Network};
+use std::{
+    io::BufRead,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+/// Parses a list of networks, one per line. Each line holds an address, such as `192.0.2.1`, or
+/// a network in CIDR notation, such as `192.0.2.0/24`, of either address family. Everything
+/// after a `#` is a comment, and blank lines are ignored.
+///
+/// Returns the networks in the order they appear in the list, or an `InvalidNetList` error for
+/// the first line that can not be parsed.
+pub fn parse(list: &str) -> Result<Vec<IpNetwork>> {
+    list.lines()
+        .enumerate()
+        .filter_map(|(index, line)| parse_line(index + 1, line).transpose())
+        .collect()
+}
+
+/// Same as `parse`, but reads the list from `reader`, without holding all of it in memory at
+/// once.
+pub fn read(reader: impl BufRead) -> Result<Vec<IpNetwork>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| match line {
+            Ok(line) => parse_line(index + 1, &line).transpose(),
+            Err(e) => Some(Err(Error::from(ErrorInternal::NetListRead(e)))),
+        })
+        .collect()
+}
+
+/// Parses a list of networks with `parse` and aggregates it with `aggregate`.
+pub fn parse_aggregated(list: &str) -> Result<Vec<IpNetwork>> {
+    parse(list).map(aggregate)
+}
+
+/// Reads a list of networks with `read` and aggregates it with `aggregate`.
+pub fn read_aggregated(reader: impl BufRead) -> Result<Vec<IpNetwork>> {
+    read(reader).map(aggregate)
+}
+
+/// Returns the smallest set of networks covering exactly the same addresses as `networks`.
+/// Overlapping and adjacent networks are merged, and bits outside of the prefix of a network are
+/// ignored.
+///
+/// The result is sorted, with all IPv4 networks before all IPv6 networks.
+pub fn aggregate(networks: impl IntoIterator<Item = IpNetwork>) -> Vec<IpNetwork> {
+    let mut v4_ranges = Vec::new();
+    let mut v6_ranges = Vec::new();
+    for network in networks {
+        match network {
+            IpNetwork::V4(network) => v4_ranges.push(range(
+                u128::from(u32::from(network.network())),
+                network.prefix(),
+                32,
+            )),
+            IpNetwork::V6(network) => {
+                v6_ranges.push(range(u128::from(network.network()), network.prefix(), 128))
+            }
+        }
+    }
+
+    let v4_networks = to_networks(merge(v4_ranges), 32).map(|(start, prefix)| {
+        let ip = Ipv4Addr::from(start as u32);
+        IpNetwork::V4(Ipv4Network::new(ip, prefix).expect("prefix must be valid for IPv4"))
+    });
+    let v6_networks = to_networks(merge(v6_ranges), 128).map(|(start, prefix)| {
+        let ip = Ipv6Addr::from(start);
+        IpNetwork::V6(Ipv6Network::new(ip, prefix).expect("prefix must be valid for IPv6"))
+    });
+    v4_networks.chain(v6_networks).collect()
+}
+
+/// Parses a single line of a list, returning `None` for lines without a network.
+fn parse_line(line_number: usize, line: &str) -> Result<Option<IpNetwork>> {
+    let entry = line.split('#').next().unwrap_or_default().trim();
+    if entry.is_empty() {
+        return Ok(None);
+    }
+    let network = if entry.contains('/') {
+        entry.parse::<IpNetwork>().ok()
+    } else {
+        entry.parse::<IpAddr>().ok().map(IpNetwork::from)
+    };
+    network
+        .map(Some)
+        .ok_or_else(|| Error::from(ErrorInternal::InvalidNetList(line_number, entry.to_owned())))
+}
+
+/// Returns the first and last address of a network in an address space of `bits` bits.
+fn range(start: u128, prefix: u8, bits: u8) -> (u128, u128) {
+    (start, start | host_mask(bits - prefix))
+}
+
+/// Returns a mask with the `host_bits` lowest bits set.
+fn host_mask(host_bits: u8) -> u128 {
+    u128::MAX
+        .checked_shr(128 - u32::from(host_bits))
+        .unwrap_or(0)
+}
+
+/// Sorts the inclusive address ranges and merges the ones that overlap or are adjacent.
+fn merge(mut ranges: Vec<(u128, u128)>) -> Vec<(u128, u128)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u128, u128)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= last_end.saturating_add(1) => {
+                *last_end = (*last_end).max(end);
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Splits inclusive address ranges into the fewest networks covering them, as pairs of network
+/// address and prefix length.
+fn to_networks(ranges: Vec<(u128, u128)>, bits: u8) -> impl Iterator<Item = (u128, u8)> {
+    ranges.into_iter().flat_map(move |(start, end)| {
+        let mut networks = Vec::new();
+        let mut start = start;
+        loop {
+            // Use the largest network that starts at `start` and does not go past `end`
+            let mut host_bits = (start.trailing_zeros() as u8).min(bits);
+            while start | host_mask(host_bits) > end {
+                host_bits -= 1;
+            }
+            networks.push((start, bits - host_bits));
+
+            let last = start | host_mask(host_bits);
+            if last >= end {
+                break networks;
+            }
+            start = last + 1;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn networks(networks: &[&str]) -> Vec<IpNetwork> {
+        networks.iter().map(|n| n.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn parse_skips_comments_and_blank_lines() {
+        let list = "# header\n\n  192.0.2.0/24  \n198.51.100.7 # a host\n\t\n2001:db8::/32\n::1\n";
+        assert_eq!(
+            parse(list).unwrap(),
+            networks(&[
+                "192.0.2.0/24",
+                "198.51.100.7/32",
+                "2001:db8::/32",
+                "::1/128"
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_reports_invalid_line() {
+        let error = parse("10.0.0.0/8\n\n10.0.0.0/33\n").unwrap_err();
+        assert_eq!(error.kind(), crate::ErrorKind::InvalidNetList);
+        assert_eq!(
+            error.to_string(),
+            "Invalid network on line 3 of address list (10.0.0.0/33)"
+        );
+        assert!(parse("not-an-address").is_err());
+    }
+
+    #[test]
+    fn read_from_reader() {
+        let list = "10.0.0.0/25\n10.0.0.128/25\n";
+        assert_eq!(
+            read_aggregated(list.as_bytes()).unwrap(),
+            networks(&["10.0.0.0/24"])
+        );
+    }
+
+    #[test]
+    fn aggregate_merges_adjacent_and_overlapping() {
+        let input = networks(&[
+            "10.0.1.0/24",
+            "10.0.0.0/24",
+            "10.0.0.128/25",
+            "10.0.2.0/23",
+            "192.168.0.1/32",
+        ]);
+        assert_eq!(
+            aggregate(input),
+            networks(&["10.0.0.0/22", "192.168.0.1/32"])
+        );
+    }
+
+    #[test]
+    fn aggregate_ignores_host_bits() {
+        assert_eq!(
+            aggregate(networks(&["10.1.2.3/8", "10.0.0.0/8"])),
+            networks(&["10.0.0.0/8"])
+        );
+    }
+
+    #[test]
+    fn aggregate_unaligned_range() {
+        // 10.0.0.1 - 10.0.0.6 can not be expressed as a single network
+        let input = networks(&["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/31", "10.0.0.6/32"]);
+        assert_eq!(
+            aggregate(input),
+            networks(&["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/31", "10.0.0.6/32"])
+        );
+        let input = networks(&["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/30", "10.0.0.0/32"]);
+        assert_eq!(aggregate(input), networks(&["10.0.0.0/29"]));
+    }
+
+    #[test]
+    fn aggregate_keeps_families_apart() {
+        let input = networks(&["fd00::/9", "10.0.0.0/8", "fd80::/9", "::ffff:10.0.0.0/104"]);
+        assert_eq!(
+            aggregate(input),
+            networks(&["10.0.0.0/8", "::ffff:10.0.0.0/104", "fd00::/8"])
+        );
+    }
+
+    #[test]
+    fn aggregate_whole_address_space() {
+        let input = networks(&["0.0.0.0/1", "128.0.0.0/1", "::/1", "8000::/1", "::1/128"]);
+        assert_eq!(aggregate(input), networks(&["0.0.0.0/0", "::/0"]));
+        assert_eq!(
+            aggregate(networks(&["255.255.255.255/32", "255.255.255.254/32"])),
+            networks(&["255.255.255.254/31"])
+        );
+    }
+}
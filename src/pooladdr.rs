@@ -9,7 +9,7 @@
 use zerocopy::FromZeros;
 
 use crate::{
-    Interface, Ip,
+    AddrFamily, Interface, Ip,
     conversion::{CopyTo, TryCopyTo},
     ffi,
 };
@@ -28,6 +28,27 @@ impl PoolAddr {
             ip: ip.into(),
         }
     }
+
+    /// Accessor for `interface`
+    pub fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    /// Accessor for `ip`
+    pub fn ip(&self) -> Ip {
+        self.ip
+    }
+
+    /// Reads a `PoolAddr` back from its FFI representation in a rule of address family `af`.
+    pub(crate) fn try_from_raw(
+        pf_pooladdr: &ffi::pfvar::pf_pooladdr,
+        af: AddrFamily,
+    ) -> Result<Self, crate::Error> {
+        Ok(PoolAddr {
+            interface: Interface::try_from_raw(&pf_pooladdr.ifname)?,
+            ip: Ip::try_from_raw(&pf_pooladdr.addr, af)?,
+        })
+    }
 }
 
 impl From<Interface> for PoolAddr {
@@ -67,20 +88,20 @@ impl TryCopyTo<ffi::pfvar::pf_pooladdr> for PoolAddr {
 ///
 /// One should never use `pf_palist` produced by this class past the lifetime expiration of it.
 pub struct PoolAddrList {
-    list: ffi::pfvar::pf_palist,
+    // Both the list head and the elements are boxed, so the pointers between them stay valid
+    // when the `PoolAddrList` is moved.
+    list: Box<ffi::pfvar::pf_palist>,
     _pool: Box<[ffi::pfvar::pf_pooladdr]>,
 }
 
 impl PoolAddrList {
     pub fn new(pool_addrs: &[PoolAddr]) -> Result<Self, crate::Error> {
-        let mut pool = Self::init_pool(pool_addrs)?;
+        let mut pool = Self::init_pool(pool_addrs)?.into_boxed_slice();
+        let mut list = Box::new(ffi::pfvar::pf_palist::new_zeroed());
         Self::link_elements(&mut pool);
-        let list = Self::create_palist(&mut pool);
+        Self::link_list(&mut list, &mut pool);
 
-        Ok(PoolAddrList {
-            list,
-            _pool: pool.into_boxed_slice(),
-        })
+        Ok(PoolAddrList { list, _pool: pool })
     }
 
     /// Returns a copy of inner pf_palist linked list.
@@ -90,7 +111,7 @@ impl PoolAddrList {
     /// Returned object has pointers into the `PoolAddrList` it was created from. So the
     /// `PoolAddrList` must outlive the returned `pf_palist`
     pub(crate) unsafe fn to_palist(&self) -> ffi::pfvar::pf_palist {
-        self.list
+        *self.list
     }
 
     fn init_pool(pool_addrs: &[PoolAddr]) -> Result<Vec<ffi::pfvar::pf_pooladdr>, crate::Error> {
@@ -105,27 +126,70 @@ impl PoolAddrList {
 
     fn link_elements(pool: &mut [ffi::pfvar::pf_pooladdr]) {
         for i in 1..pool.len() {
-            let mut elem1 = pool[i - 1];
-            let mut elem2 = pool[i];
-            elem1.entries.tqe_next = &mut elem2;
+            let (head, tail) = pool.split_at_mut(i);
+            let elem1 = &mut head[i - 1];
+            let elem2 = &mut tail[0];
+            elem1.entries.tqe_next = elem2;
             elem2.entries.tqe_prev = &mut elem1.entries.tqe_next;
         }
     }
 
-    fn create_palist(pool: &mut [ffi::pfvar::pf_pooladdr]) -> ffi::pfvar::pf_palist {
-        let mut list = ffi::pfvar::pf_palist::new_zeroed();
-        if !pool.is_empty() {
-            let mut first_elem = pool[0];
-            let mut last_elem = pool[pool.len() - 1];
-
-            list.tqh_first = &mut first_elem;
-            first_elem.entries.tqe_prev = &mut list.tqh_first;
+    fn link_list(list: &mut ffi::pfvar::pf_palist, pool: &mut [ffi::pfvar::pf_pooladdr]) {
+        if let Some(last_elem) = pool.last_mut() {
             last_elem.entries.tqe_next = ptr::null_mut();
             list.tqh_last = &mut last_elem.entries.tqe_next;
+
+            let first_elem = &mut pool[0];
+            first_elem.entries.tqe_prev = &mut list.tqh_first;
+            list.tqh_first = first_elem;
         } else {
             list.tqh_first = ptr::null_mut();
             list.tqh_last = &mut list.tqh_first;
         }
-        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    /// Walks `list` from its head and returns the addresses in it, checking that every back
+    /// pointer points to the forward pointer leading to its element.
+    fn walk(list: &ffi::pfvar::pf_palist) -> Vec<Ip> {
+        let mut ips = Vec::new();
+        let mut next_ptr = ptr::addr_of!(list.tqh_first).cast_mut();
+        let mut elem = list.tqh_first;
+        while !elem.is_null() {
+            // SAFETY: The elements are owned by the `PoolAddrList` the list is borrowed from.
+            let pf_pooladdr = unsafe { &mut *elem };
+            assert_eq!(pf_pooladdr.entries.tqe_prev, next_ptr);
+            ips.push(Ip::try_from_raw(&pf_pooladdr.addr, AddrFamily::Ipv4).unwrap());
+            next_ptr = &mut pf_pooladdr.entries.tqe_next;
+            elem = pf_pooladdr.entries.tqe_next;
+        }
+        assert_eq!(list.tqh_last, next_ptr);
+        ips
+    }
+
+    #[test]
+    fn pool_addr_list_links_elements_in_place() {
+        let ips: Vec<Ip> = (1..=3)
+            .map(|i| Ip::from(Ipv4Addr::new(10, 0, 0, i)))
+            .collect();
+        let pool_addrs: Vec<PoolAddr> = ips.iter().copied().map(PoolAddr::from).collect();
+        // Moving the lists into an array must not invalidate the pointers in them
+        let lists = [
+            PoolAddrList::new(&pool_addrs).unwrap(),
+            PoolAddrList::new(&pool_addrs[..1]).unwrap(),
+        ];
+        assert_eq!(walk(&lists[0].list), ips);
+        assert_eq!(walk(&lists[1].list), ips[..1]);
+    }
+
+    #[test]
+    fn empty_pool_addr_list() {
+        let list = PoolAddrList::new(&[]).unwrap();
+        assert!(walk(&list.list).is_empty());
     }
 }
@@ -0,0 +1,74 @@
+// Copyright 2025 Mullvad VPN AB.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{Endpoint, Interface, Proto};
+use crate::{Error, ErrorInternal, Result};
+use std::ops::Deref;
+
+/// A list of alternatives for a field of a rule, written as `{ a, b }` in pf.conf. The rule
+/// matches if any of the alternatives match. PF has no such lists itself, so a rule with lists is
+/// expanded into one rule for every combination of alternatives. See `FilterRule::expand`.
+///
+/// Setting a single value on a builder gives a list with only that value.
+///
+/// Lists are only supported in filter rules. `NatRule` and `RedirectRule` take single values, so
+/// NAT and redirect rules for several alternatives have to be built one by one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnyOf<T>(Vec<T>);
+
+impl<T> AnyOf<T> {
+    /// Returns the only alternative, or an `InvalidRuleCombination` error if the list has to be
+    /// expanded before the rule can be sent to PF.
+    pub(crate) fn single(&self) -> Result<&T> {
+        match self.0[..] {
+            [ref value] => Ok(value),
+            _ => {
+                let msg = "Rules with lists have to be expanded with FilterRule::expand".to_owned();
+                Err(Error::from(ErrorInternal::InvalidRuleCombination(msg)))
+            }
+        }
+    }
+}
+
+impl<T> Deref for AnyOf<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T: Default> Default for AnyOf<T> {
+    fn default() -> Self {
+        AnyOf(vec![T::default()])
+    }
+}
+
+impl<T> FromIterator<T> for AnyOf<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        AnyOf(iter.into_iter().collect())
+    }
+}
+
+impl<T: Into<Interface>> From<T> for AnyOf<Interface> {
+    fn from(interface: T) -> Self {
+        AnyOf(vec![interface.into()])
+    }
+}
+
+impl From<Proto> for AnyOf<Proto> {
+    fn from(proto: Proto) -> Self {
+        AnyOf(vec![proto.normalized()])
+    }
+}
+
+impl<T: Into<Endpoint>> From<T> for AnyOf<Endpoint> {
+    fn from(endpoint: T) -> Self {
+        AnyOf(vec![endpoint.into()])
+    }
+}
@@ -8,6 +8,7 @@
 
 use super::{AddrFamily, Ip, Port};
 use crate::{
+    Result, TableName,
     conversion::{CopyTo, TryCopyTo},
     ffi,
 };
@@ -17,6 +18,7 @@ use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV
 pub struct Endpoint {
     ip: Ip,
     port: Port,
+    negated: bool,
 }
 
 impl Endpoint {
@@ -24,6 +26,16 @@ impl Endpoint {
         Endpoint {
             ip: ip.into(),
             port: port.into(),
+            negated: false,
+        }
+    }
+
+    /// Returns an `Endpoint` matching all addresses except `ip`, on `port`. Written as
+    /// `! 10.0.0.0/8` in pf.conf. Only the address is negated, not the port.
+    pub fn not<IP: Into<Ip>, PORT: Into<Port>>(ip: IP, port: PORT) -> Self {
+        Endpoint {
+            negated: true,
+            ..Endpoint::new(ip, port)
         }
     }
 
@@ -35,9 +47,34 @@ impl Endpoint {
         self.port
     }
 
+    /// Returns this endpoint with its port replaced by `port`.
+    pub(crate) fn with_port(self, port: Port) -> Self {
+        Endpoint { port, ..self }
+    }
+
+    /// Returns true if this endpoint matches all addresses except `ip`.
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Returns the address family of `ip`. A negated address still restricts the rule to its
+    /// family, like in pf.conf.
     pub fn get_af(&self) -> AddrFamily {
         self.ip.get_af()
     }
+
+    /// Reads an `Endpoint` back from its FFI representation in a rule of address family `af`.
+    pub(crate) fn try_from_raw(
+        pf_rule_addr: &ffi::pfvar::pf_rule_addr,
+        af: AddrFamily,
+    ) -> Result<Self> {
+        let ip = Ip::try_from_raw(&pf_rule_addr.addr, af)?;
+        // SAFETY: `range` is the union field written by `try_copy_to`, and any bit pattern is a
+        // valid `pf_port_range`.
+        let port = Port::try_from(unsafe { pf_rule_addr.xport.range })?;
+        let negated = pf_rule_addr.neg != 0;
+        Ok(Endpoint { ip, port, negated })
+    }
 }
 
 impl From<Ip> for Endpoint {
@@ -70,6 +107,12 @@ impl From<IpAddr> for Endpoint {
     }
 }
 
+impl From<TableName> for Endpoint {
+    fn from(name: TableName) -> Self {
+        Self::from(Ip::from(name))
+    }
+}
+
 impl From<SocketAddrV4> for Endpoint {
     fn from(socket_addr: SocketAddrV4) -> Self {
         Endpoint::new(Ip::from(*socket_addr.ip()), Port::from(socket_addr.port()))
@@ -95,7 +138,9 @@ impl TryCopyTo<ffi::pfvar::pf_rule_addr> for Endpoint {
     type Error = crate::Error;
 
     fn try_copy_to(&self, pf_rule_addr: &mut ffi::pfvar::pf_rule_addr) -> crate::Result<()> {
+        self.ip.validate()?;
         self.ip.copy_to(&mut pf_rule_addr.addr);
+        pf_rule_addr.neg = self.negated as u8;
         self.port
             .try_copy_to(unsafe { &mut pf_rule_addr.xport.range })?;
         Ok(())
@@ -0,0 +1,194 @@
+// Copyright 2025 Mullvad VPN AB.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{PoolAddr, Result, conversion::TryCopyTo, ffi};
+use std::{fmt, hash::Hasher};
+use zerocopy::FromZeros;
+
+/// Version of the encoding fingerprints are computed from. Bumped whenever the encoding changes,
+/// which changes the fingerprints of all rules.
+const ENCODING_VERSION: u8 = 1;
+
+/// A hash identifying a rule by its content rather than by its position in an anchor.
+///
+/// The fingerprint is computed from the rule as PF stores it, so a rule built with one of the
+/// builders gets the same fingerprint as the same rule read back from PF, for example with
+/// `PfCtl::get_filter_rules`. It is the 64 bit FNV-1a hash of a version byte followed by the
+/// fields of the `pf_rule` this crate sets, in a fixed order and byte order, and the address the
+/// rule translates or routes to, if any. Fingerprints are therefore the same across processes,
+/// platforms and versions of this crate, unless a release notes a change of the encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RuleFingerprint(u64);
+
+impl RuleFingerprint {
+    /// Computes the fingerprint of a rule from its `pf_rule`, as written by `TryCopyTo`, and the
+    /// pool address of the rule.
+    pub(crate) fn new(pf_rule: &ffi::pfvar::pf_rule, pool_addr: Option<&PoolAddr>) -> Result<Self> {
+        let mut hasher = Fnv1aHasher::default();
+        hasher.write(&[ENCODING_VERSION]);
+        write_rule(&mut hasher, pf_rule);
+        match pool_addr {
+            None => hasher.write(&[0]),
+            Some(pool_addr) => {
+                let mut pf_pooladdr = ffi::pfvar::pf_pooladdr::new_zeroed();
+                pool_addr.try_copy_to(&mut pf_pooladdr)?;
+                hasher.write(&[1]);
+                write_c_chars(&mut hasher, &pf_pooladdr.ifname);
+                write_addr_wrap(&mut hasher, &pf_pooladdr.addr);
+            }
+        }
+        Ok(RuleFingerprint(hasher.finish()))
+    }
+
+    /// Returns the fingerprint as an integer.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for RuleFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Encodes the fields of `pf_rule` that rules of this crate can set. Multi-byte integers in host
+/// byte order are written in big endian, ports PF keeps in network byte order are written as is.
+fn write_rule(hasher: &mut Fnv1aHasher, pf_rule: &ffi::pfvar::pf_rule) {
+    hasher.write(&[
+        pf_rule.action,
+        pf_rule.direction,
+        pf_rule.quick,
+        pf_rule.log,
+        pf_rule.rt,
+        pf_rule.keep_state,
+        pf_rule.flags,
+        pf_rule.flagset,
+    ]);
+    hasher.write(&pf_rule.rule_flag.to_be_bytes());
+    write_c_chars(hasher, &pf_rule.ifname);
+    hasher.write(&[pf_rule.ifnot, pf_rule.proto, pf_rule.af]);
+    write_rule_addr(hasher, &pf_rule.src);
+    write_rule_addr(hasher, &pf_rule.dst);
+    write_c_chars(hasher, &pf_rule.label);
+    for id in pf_rule.uid.uid.iter().chain(&pf_rule.gid.gid) {
+        hasher.write(&id.to_be_bytes());
+    }
+    hasher.write(&[pf_rule.uid.op, pf_rule.gid.op, pf_rule.type_, pf_rule.code]);
+    for port in pf_rule.rpool.proxy_port {
+        hasher.write(&port.to_be_bytes());
+    }
+    hasher.write(&[pf_rule.rpool.port_op]);
+}
+
+fn write_rule_addr(hasher: &mut Fnv1aHasher, pf_rule_addr: &ffi::pfvar::pf_rule_addr) {
+    write_addr_wrap(hasher, &pf_rule_addr.addr);
+    // SAFETY: `range` is the union field written by `Port`, and any bit pattern is a valid
+    // `pf_port_range`.
+    let range = unsafe { pf_rule_addr.xport.range };
+    for port in range.port {
+        hasher.write(&port.to_ne_bytes());
+    }
+    hasher.write(&[range.op, pf_rule_addr.neg]);
+}
+
+fn write_addr_wrap(hasher: &mut Fnv1aHasher, pf_addr_wrap: &ffi::pfvar::pf_addr_wrap) {
+    hasher.write(&[pf_addr_wrap.type_, pf_addr_wrap.iflags]);
+    // SAFETY: The address and mask cover the whole union, whichever field was written, and
+    // any bit pattern is a valid byte array.
+    let (addr, mask) = unsafe {
+        (
+            pf_addr_wrap.v.a.addr.pfa._addr8,
+            pf_addr_wrap.v.a.mask.pfa._addr8,
+        )
+    };
+    hasher.write(&addr);
+    hasher.write(&mask);
+}
+
+fn write_c_chars(hasher: &mut Fnv1aHasher, chars: &[std::os::raw::c_char]) {
+    for &c in chars {
+        hasher.write(&[c as u8]);
+    }
+}
+
+/// 64 bit FNV-1a. Unlike the hasher in the standard library its algorithm is fixed, which is what
+/// makes fingerprints reproducible together with the explicit encoding of rules.
+struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Direction, Endpoint, FilterRuleAction, FilterRuleBuilder, Proto, RedirectRuleAction,
+        RedirectRuleBuilder,
+    };
+    use std::net::Ipv4Addr;
+
+    // Fingerprints are meant to be stored and compared across versions of this crate, so a
+    // change of these values has to come with a new `ENCODING_VERSION` and a changelog entry.
+    #[test]
+    fn fingerprint_golden_values() {
+        let filter_rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .direction(Direction::Out)
+            .quick(true)
+            .proto(Proto::Tcp)
+            .to(Endpoint::new(Ipv4Addr::new(192, 168, 0, 1), 80))
+            .label("golden")
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter_rule.fingerprint().unwrap().as_u64(),
+            0xe7d2_f09b_bb8e_7481
+        );
+
+        let redirect_rule = RedirectRuleBuilder::default()
+            .action(RedirectRuleAction::Redirect)
+            .proto(Proto::Tcp)
+            .to(Endpoint::new(Ipv4Addr::new(1, 2, 3, 4), 443))
+            .redirect_to(Endpoint::new(Ipv4Addr::LOCALHOST, 8443))
+            .build()
+            .unwrap();
+        assert_eq!(
+            redirect_rule.fingerprint().unwrap().as_u64(),
+            0x3287_b4cd_75df_454a
+        );
+    }
+
+    #[test]
+    fn fnv1a_reference_values() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = Fnv1aHasher::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -8,6 +8,7 @@
 
 pub use super::uid::Id;
 use crate::{
+    Result,
     conversion::CopyTo,
     ffi::pfvar::{PF_OP_NONE, pf_rule_gid},
 };
@@ -27,6 +28,14 @@ impl<T: Into<Id>> From<T> for Gid {
     }
 }
 
+impl TryFrom<pf_rule_gid> for Gid {
+    type Error = crate::Error;
+
+    fn try_from(pf_rule_gid: pf_rule_gid) -> Result<Self> {
+        Id::try_from_raw(pf_rule_gid.gid, pf_rule_gid.op).map(Gid)
+    }
+}
+
 impl CopyTo<pf_rule_gid> for Gid {
     fn copy_to(&self, pf_rule_gid: &mut pf_rule_gid) {
         match self.0 {
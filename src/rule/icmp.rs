@@ -95,6 +95,59 @@ impl IcmpType {
     }
 }
 
+impl IcmpType {
+    /// Reads an ICMP type back from the `type_` and `code` fields of a `pf_rule`.
+    /// Returns `None` if the rule does not match on ICMP type.
+    pub(crate) fn try_from_raw(type_: u8, code: u8) -> crate::Result<Option<Self>> {
+        use IcmpType::*;
+        // The fields are stored one higher than the constants, zero means unset.
+        let Some(raw_type) = type_.checked_sub(1) else {
+            return Ok(None);
+        };
+        let raw_code = code.checked_sub(1);
+        let icmp_type = match (raw_type, raw_code) {
+            (0, None) => Some(EchoRep),
+            (3, Some(code)) => IcmpUnreachCode::from_raw(code).map(Unreach),
+            (8, None) => Some(EchoReq),
+            (11, Some(code)) => IcmpTimexCode::from_raw(code).map(Timex),
+            (30, None) => Some(Trace),
+            (icmp6_type, None) => Icmp6Type::from_raw(icmp6_type).map(Icmp6),
+            _ => None,
+        };
+        icmp_type.map(Some).ok_or_else(|| {
+            let msg = format!("ICMP type {raw_type} with code {raw_code:?} is not supported");
+            crate::Error::from(crate::ErrorInternal::UnsupportedRule(msg))
+        })
+    }
+}
+
+impl IcmpUnreachCode {
+    fn from_raw(code: u8) -> Option<Self> {
+        use IcmpUnreachCode::*;
+        [NetUnreach, HostUnreach, ProtoUnreach, PortUnreach, NeedFrag]
+            .into_iter()
+            .find(|&unreach_code| unreach_code as u8 == code)
+    }
+}
+
+impl IcmpTimexCode {
+    fn from_raw(code: u8) -> Option<Self> {
+        use IcmpTimexCode::*;
+        [Transit, Reassembly]
+            .into_iter()
+            .find(|&timex_code| timex_code as u8 == code)
+    }
+}
+
+impl Icmp6Type {
+    fn from_raw(icmp6_type: u8) -> Option<Self> {
+        use Icmp6Type::*;
+        [RouterSol, RouterAdv, NeighbrSol, NeighbrAdv, Redir]
+            .into_iter()
+            .find(|&icmp6| icmp6 as u8 == icmp6_type)
+    }
+}
+
 impl crate::conversion::CopyTo<crate::ffi::pfvar::pf_rule> for IcmpType {
     fn copy_to(&self, pf_rule: &mut crate::ffi::pfvar::pf_rule) {
         // The field should be set to one higher than the constants.
@@ -8,7 +8,16 @@
 
 use zerocopy::transmute_ref;
 
-use crate::{Error, ErrorInternal, conversion::TryCopyTo};
+use crate::{Error, ErrorInternal, conversion::TryCopyTo, ffi, utils::FixedName};
+
+/// Size of the interface name buffers in PF, including the terminating null byte. Taken from
+/// the bindings so it can not get out of sync with them.
+pub(crate) const IFNAMSIZ: usize = array_len(|pf_rule: &ffi::pfvar::pf_rule| &pf_rule.ifname);
+
+/// Returns the length of the array returned by `field`.
+const fn array_len<S, T, const N: usize>(_field: fn(&S) -> &[T; N]) -> usize {
+    N
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct InterfaceName(String);
@@ -24,6 +33,9 @@ pub enum Interface {
     #[default]
     Any,
     Name(InterfaceName),
+    /// Any interface except the named one. Written as `on ! en0` in pf.conf. Only rules can
+    /// match on a negated interface.
+    Not(InterfaceName),
 }
 
 impl<T: AsRef<str>> From<T> for Interface {
@@ -32,16 +44,140 @@ impl<T: AsRef<str>> From<T> for Interface {
     }
 }
 
+impl Interface {
+    /// Returns an `Interface` matching any interface except `name`.
+    pub fn not<T: AsRef<str>>(name: T) -> Self {
+        Interface::Not(InterfaceName(name.as_ref().to_owned()))
+    }
+
+    /// Returns true if this matches any interface except the named one.
+    pub fn is_negated(&self) -> bool {
+        matches!(self, Interface::Not(_))
+    }
+
+    /// Reads an `Interface` back from a raw interface name. An empty name means any interface.
+    pub(crate) fn try_from_raw(ifname: &[std::os::raw::c_char]) -> Result<Self, crate::Error> {
+        let name = interface_name_from_raw(ifname)?;
+        if name.is_empty() {
+            Ok(Interface::Any)
+        } else {
+            Ok(Interface::from(name))
+        }
+    }
+
+    /// Reads the `Interface` of a rule back from its `ifname` and `ifnot` fields.
+    pub(crate) fn try_from_rule(pf_rule: &ffi::pfvar::pf_rule) -> Result<Self, crate::Error> {
+        match Interface::try_from_raw(&pf_rule.ifname)? {
+            Interface::Name(name) if pf_rule.ifnot != 0 => Ok(Interface::Not(name)),
+            interface => Ok(interface),
+        }
+    }
+
+    /// Copies the `Interface` to the `ifname` and `ifnot` fields of a rule.
+    pub(crate) fn try_copy_to_rule(
+        &self,
+        pf_rule: &mut ffi::pfvar::pf_rule,
+    ) -> Result<(), crate::Error> {
+        let name = match self {
+            Interface::Any => "",
+            Interface::Name(InterfaceName(name)) | Interface::Not(InterfaceName(name)) => name,
+        };
+        copy_interface_name(name, &mut pf_rule.ifname)?;
+        pf_rule.ifnot = self.is_negated() as u8;
+        Ok(())
+    }
+}
+
 impl TryCopyTo<[i8]> for Interface {
     type Error = crate::Error;
 
     fn try_copy_to(&self, dst: &mut [i8]) -> Result<(), Self::Error> {
         match *self {
-            Interface::Any => "",
-            Interface::Name(InterfaceName(ref name)) => &name[..],
+            Interface::Any => copy_interface_name("", dst),
+            Interface::Name(InterfaceName(ref name)) => copy_interface_name(name, dst),
+            Interface::Not(_) => Err(Error::from(ErrorInternal::InvalidInterfaceName(
+                "negation is only supported in rules",
+            ))),
         }
-        .try_copy_to(dst)
+    }
+}
+
+/// Copies an interface name into a PF interface name buffer. This is the validation shared by
+/// all interface name types, an empty name means any interface.
+fn copy_interface_name(name: &str, dst: &mut [i8]) -> Result<(), crate::Error> {
+    name.try_copy_to(dst)
         .map_err(|reason| Error::from(ErrorInternal::InvalidInterfaceName(reason)))
+}
+
+/// Reads an interface name back from a raw, null terminated, PF interface name buffer.
+fn interface_name_from_raw(ifname: &[std::os::raw::c_char]) -> Result<String, crate::Error> {
+    crate::cstr_to_string(ifname)
+        .map_err(|reason| Error::from(ErrorInternal::InvalidInterfaceName(reason)))
+}
+
+/// The name of an interface whose addresses an `Ip::Interface` endpoint matches.
+///
+/// The name is validated on creation to fit into PF's fixed size interface name buffer, which
+/// lets `InterfaceAddrName`, and the rule types containing it, stay `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InterfaceAddrName(FixedName<IFNAMSIZ>);
+
+impl InterfaceAddrName {
+    /// Returns an `InterfaceAddrName` if `name` is a valid interface name, meaning it is not
+    /// empty, does not contain any null byte and is shorter than `IFNAMSIZ` bytes.
+    pub fn new(name: &str) -> crate::Result<Self> {
+        FixedName::new(name, ErrorInternal::InvalidInterfaceName).map(InterfaceAddrName)
+    }
+
+    /// Reads an `InterfaceAddrName` back from a raw, null terminated, interface name.
+    pub(crate) fn try_from_raw(name: &[std::os::raw::c_char]) -> crate::Result<Self> {
+        FixedName::try_from_raw(name, ErrorInternal::InvalidInterfaceName).map(InterfaceAddrName)
+    }
+}
+
+impl_fixed_name!(InterfaceAddrName);
+
+/// Which addresses of an interface an `Ip::Interface` endpoint matches. Written as a suffix to
+/// the interface name in pf.conf, such as `(en0:network)`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InterfaceAddrModifier {
+    /// All addresses of the interface, including aliases. Written as `(en0)`.
+    #[default]
+    Address,
+    /// The networks the addresses of the interface are in. Written as `(en0:network)`.
+    Network,
+    /// The broadcast addresses of the interface. Written as `(en0:broadcast)`.
+    Broadcast,
+    /// The addresses of the peers of a point-to-point interface. Written as `(en0:peer)`.
+    Peer,
+    /// The addresses of the interface, excluding aliases. Written as `(en0:0)`.
+    NoAlias,
+}
+
+impl InterfaceAddrModifier {
+    /// Returns the `PFI_AFLAG_*` flags of `pf_addr_wrap::iflags` for this modifier.
+    pub(crate) fn to_iflags(self) -> u8 {
+        let iflags = match self {
+            InterfaceAddrModifier::Address => 0,
+            InterfaceAddrModifier::Network => ffi::pfvar::PFI_AFLAG_NETWORK,
+            InterfaceAddrModifier::Broadcast => ffi::pfvar::PFI_AFLAG_BROADCAST,
+            InterfaceAddrModifier::Peer => ffi::pfvar::PFI_AFLAG_PEER,
+            InterfaceAddrModifier::NoAlias => ffi::pfvar::PFI_AFLAG_NOALIAS,
+        };
+        iflags as u8
+    }
+
+    /// Reads the modifier back from `pf_addr_wrap::iflags`. Returns `None` for combinations of
+    /// flags that are not covered by a single modifier, such as `(en0:network:0)`.
+    pub(crate) fn from_iflags(iflags: u8) -> Option<Self> {
+        match u32::from(iflags) {
+            0 => Some(InterfaceAddrModifier::Address),
+            ffi::pfvar::PFI_AFLAG_NETWORK => Some(InterfaceAddrModifier::Network),
+            ffi::pfvar::PFI_AFLAG_BROADCAST => Some(InterfaceAddrModifier::Broadcast),
+            ffi::pfvar::PFI_AFLAG_PEER => Some(InterfaceAddrModifier::Peer),
+            ffi::pfvar::PFI_AFLAG_NOALIAS => Some(InterfaceAddrModifier::NoAlias),
+            _ => None,
+        }
     }
 }
 
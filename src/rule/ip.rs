@@ -7,7 +7,7 @@
 // except according to those terms.
 
 use crate::{
-    AddrFamily, Result,
+    AddrFamily, Error, ErrorInternal, InterfaceAddrModifier, InterfaceAddrName, Result, TableName,
     conversion::CopyTo,
     ffi,
     pooladdr::{PoolAddr, PoolAddrList},
@@ -20,6 +20,24 @@ pub enum Ip {
     #[default]
     Any,
     Net(IpNetwork),
+    /// Any address in the PF table with the given name, in the anchor of the rule. Written as
+    /// `<name>` in pf.conf.
+    Table(TableName),
+    /// The addresses currently assigned to an interface, tracked by PF as they change. Written
+    /// as `(name)` in pf.conf, with `modifier` selecting which of the addresses to match.
+    Interface {
+        name: InterfaceAddrName,
+        modifier: InterfaceAddrModifier,
+    },
+    /// All addresses from the first to the second address, both included. Written as
+    /// `10.0.0.10 - 10.0.0.50` in pf.conf. Both addresses must be of the same family.
+    Range(IpAddr, IpAddr),
+    /// Any address PF has no route to. Written as `no-route` in pf.conf.
+    NoRoute,
+    /// Any address failing a unicast reverse path forwarding check, meaning the route back to it
+    /// does not go out on the interface the packet came in on. Written as `urpf-failed` in
+    /// pf.conf.
+    UrpfFailed,
 }
 
 impl Ip {
@@ -28,9 +46,43 @@ impl Ip {
             Ip::Any => AddrFamily::Any,
             Ip::Net(IpNetwork::V4(_)) => AddrFamily::Ipv4,
             Ip::Net(IpNetwork::V6(_)) => AddrFamily::Ipv6,
+            // A table can hold addresses of both families
+            Ip::Table(_) => AddrFamily::Any,
+            // The address family is taken from the rule, PF only tracks addresses of that family
+            Ip::Interface { .. } => AddrFamily::Any,
+            Ip::Range(IpAddr::V4(_), _) => AddrFamily::Ipv4,
+            Ip::Range(IpAddr::V6(_), _) => AddrFamily::Ipv6,
+            // Route lookups are done for addresses of both families
+            Ip::NoRoute | Ip::UrpfFailed => AddrFamily::Any,
         }
     }
 
+    /// Returns an `InvalidRuleCombination` error if this `Ip` can not be used in a rule, which is
+    /// the case for ranges with addresses of different families or with the end before the
+    /// start.
+    pub(crate) fn validate(&self) -> Result<()> {
+        if let Ip::Range(start, end) = *self {
+            let msg = match (start, end) {
+                (IpAddr::V4(_), IpAddr::V6(_)) | (IpAddr::V6(_), IpAddr::V4(_)) => {
+                    format!("Range {start} - {end} mixes address families")
+                }
+                _ if start > end => format!("Range {start} - {end} ends before it starts"),
+                _ => return Ok(()),
+            };
+            return Err(Error::from(ErrorInternal::InvalidRuleCombination(msg)));
+        }
+        Ok(())
+    }
+
+    /// Returns an `Ip::Interface` matching the addresses of the interface `name` selected by
+    /// `modifier`. Fails if `name` is not a valid interface name.
+    pub fn interface(name: &str, modifier: InterfaceAddrModifier) -> Result<Self> {
+        Ok(Ip::Interface {
+            name: InterfaceAddrName::new(name)?,
+            modifier,
+        })
+    }
+
     /// Returns `Ip::Any` represented an as an `IpNetwork`, used for ffi.
     fn any_ffi_repr() -> IpNetwork {
         IpNetwork::V6(Ipv6Network::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 0).unwrap())
@@ -40,6 +92,92 @@ impl Ip {
     pub fn to_pool_addr_list(&self) -> Result<PoolAddrList> {
         PoolAddrList::new(&[PoolAddr::from(*self)])
     }
+
+    /// Reads an `Ip` back from its FFI representation. `pf_addr_wrap` does not carry an address
+    /// family of its own, so the one of the rule it belongs to has to be supplied.
+    pub(crate) fn try_from_raw(
+        pf_addr_wrap: &ffi::pfvar::pf_addr_wrap,
+        af: AddrFamily,
+    ) -> Result<Self> {
+        if pf_addr_wrap.type_ == ffi::pfvar::PF_ADDR_TABLE as u8 {
+            // SAFETY: `v.tblname` is the active union field for addresses of type
+            // `PF_ADDR_TABLE`.
+            return TableName::try_from_raw(unsafe { &pf_addr_wrap.v.tblname }).map(Ip::Table);
+        }
+        if pf_addr_wrap.type_ == ffi::pfvar::PF_ADDR_NOROUTE as u8 {
+            return Ok(Ip::NoRoute);
+        }
+        if pf_addr_wrap.type_ == ffi::pfvar::PF_ADDR_URPFFAILED as u8 {
+            return Ok(Ip::UrpfFailed);
+        }
+        if pf_addr_wrap.type_ == ffi::pfvar::PF_ADDR_DYNIFTL as u8 {
+            return Self::interface_from_raw(pf_addr_wrap);
+        }
+        if pf_addr_wrap.type_ == ffi::pfvar::PF_ADDR_RANGE as u8 {
+            // SAFETY: `v.a` is the active union field for addresses of type `PF_ADDR_RANGE`,
+            // holding the first and last address of the range.
+            let (start, end) = unsafe { (pf_addr_wrap.v.a.addr, pf_addr_wrap.v.a.mask) };
+            return Ok(Ip::Range(
+                Self::addr_from_raw(&start, af)?,
+                Self::addr_from_raw(&end, af)?,
+            ));
+        }
+        if pf_addr_wrap.type_ != ffi::pfvar::PF_ADDR_ADDRMASK as u8 {
+            let msg = format!("address type {} is not supported", pf_addr_wrap.type_);
+            return Err(Error::from(ErrorInternal::UnsupportedRule(msg)));
+        }
+        // SAFETY: `v.a` is the active union field for addresses of type `PF_ADDR_ADDRMASK`.
+        let (addr, mask) = unsafe { (pf_addr_wrap.v.a.addr, pf_addr_wrap.v.a.mask) };
+        // SAFETY: All fields of `pf_addr` are plain byte arrays, so any bit pattern is valid.
+        let (addr_bytes, mask_bytes) = unsafe { (addr.pfa._addr8, mask.pfa._addr8) };
+        if addr_bytes == [0; 16] && mask_bytes == [0; 16] {
+            return Ok(Ip::Any);
+        }
+
+        let (ip, mask) = (
+            Self::addr_from_raw(&addr, af)?,
+            Self::addr_from_raw(&mask, af)?,
+        );
+        IpNetwork::with_netmask(ip, mask).map(Ip::Net).map_err(|_| {
+            let msg = format!("netmask {mask} is not contiguous");
+            Error::from(ErrorInternal::UnsupportedRule(msg))
+        })
+    }
+
+    /// Reads a single address of family `af` back from a `pf_addr`.
+    fn addr_from_raw(pf_addr: &ffi::pfvar::pf_addr, af: AddrFamily) -> Result<IpAddr> {
+        // SAFETY: All fields of `pf_addr` are plain byte arrays, so any bit pattern is valid.
+        let bytes = unsafe { pf_addr.pfa._addr8 };
+        match af {
+            // IPv4 addresses are stored in network byte order in the first four bytes
+            AddrFamily::Ipv4 => Ok(IpAddr::from([bytes[0], bytes[1], bytes[2], bytes[3]])),
+            AddrFamily::Ipv6 => Ok(IpAddr::from(Ipv6Addr::from(bytes))),
+            AddrFamily::Any => {
+                let msg = "address without address family".to_owned();
+                Err(Error::from(ErrorInternal::UnsupportedRule(msg)))
+            }
+        }
+    }
+
+    fn interface_from_raw(pf_addr_wrap: &ffi::pfvar::pf_addr_wrap) -> Result<Self> {
+        // SAFETY: `v.ifname` and `v.a.mask` do not overlap and are both used for addresses of
+        // type `PF_ADDR_DYNIFTL`. All fields of `pf_addr` are plain byte arrays.
+        let (ifname, mask_bytes) =
+            unsafe { (pf_addr_wrap.v.ifname, pf_addr_wrap.v.a.mask.pfa._addr8) };
+        if mask_bytes != [0xff; 16] {
+            let msg = "interface address with a prefix length".to_owned();
+            return Err(Error::from(ErrorInternal::UnsupportedRule(msg)));
+        }
+        let modifier =
+            InterfaceAddrModifier::from_iflags(pf_addr_wrap.iflags).ok_or_else(|| {
+                let msg = format!("interface address flags {:#x}", pf_addr_wrap.iflags);
+                Error::from(ErrorInternal::UnsupportedRule(msg))
+            })?;
+        Ok(Ip::Interface {
+            name: InterfaceAddrName::try_from_raw(&ifname)?,
+            modifier,
+        })
+    }
 }
 
 impl From<IpNetwork> for Ip {
@@ -48,6 +186,12 @@ impl From<IpNetwork> for Ip {
     }
 }
 
+impl From<TableName> for Ip {
+    fn from(name: TableName) -> Self {
+        Ip::Table(name)
+    }
+}
+
 impl From<Ipv4Addr> for Ip {
     fn from(ip: Ipv4Addr) -> Self {
         Ip::Net(IpNetwork::V4(Ipv4Network::new(ip, 32).unwrap()))
@@ -74,6 +218,25 @@ impl CopyTo<ffi::pfvar::pf_addr_wrap> for Ip {
         match *self {
             Ip::Any => Self::any_ffi_repr().copy_to(pf_addr_wrap),
             Ip::Net(net) => net.copy_to(pf_addr_wrap),
+            Ip::Table(name) => {
+                pf_addr_wrap.type_ = ffi::pfvar::PF_ADDR_TABLE as u8;
+                name.copy_to(unsafe { &mut pf_addr_wrap.v.tblname[..] });
+            }
+            Ip::Interface { name, modifier } => {
+                pf_addr_wrap.type_ = ffi::pfvar::PF_ADDR_DYNIFTL as u8;
+                pf_addr_wrap.iflags = modifier.to_iflags();
+                name.copy_to(unsafe { &mut pf_addr_wrap.v.ifname[..] });
+                // PF uses the mask as prefix length of the tracked addresses. Like pfctl, match
+                // whole addresses.
+                pf_addr_wrap.v.a.mask.pfa._addr8 = [0xff; 16];
+            }
+            Ip::Range(start, end) => {
+                pf_addr_wrap.type_ = ffi::pfvar::PF_ADDR_RANGE as u8;
+                start.copy_to(unsafe { &mut pf_addr_wrap.v.a.addr });
+                end.copy_to(unsafe { &mut pf_addr_wrap.v.a.mask });
+            }
+            Ip::NoRoute => pf_addr_wrap.type_ = ffi::pfvar::PF_ADDR_NOROUTE as u8,
+            Ip::UrpfFailed => pf_addr_wrap.type_ = ffi::pfvar::PF_ADDR_URPFFAILED as u8,
         }
     }
 }
@@ -7,7 +7,7 @@
 // except according to those terms.
 
 use crate::{
-    Error, ErrorInternal, Result,
+    Error, ErrorInternal, PoolAddr, Result, RulesetKind,
     conversion::{CopyTo, TryCopyTo},
     ffi,
 };
@@ -16,10 +16,14 @@ use std::{
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
     ops::Deref,
 };
+use zerocopy::FromZeros;
 
 mod addr_family;
 pub use self::addr_family::*;
 
+mod any_of;
+pub use self::any_of::*;
+
 mod direction;
 pub use self::direction::*;
 
@@ -59,6 +63,9 @@ pub use self::rule_action::*;
 mod rule_log;
 pub use self::rule_log::*;
 
+mod fingerprint;
+pub use self::fingerprint::*;
+
 mod uid;
 pub use self::uid::*;
 
@@ -78,15 +85,19 @@ pub struct FilterRule {
     #[builder(default)]
     keep_state: StatePolicy,
     #[builder(default)]
-    interface: Interface,
+    interface: AnyOf<Interface>,
     #[builder(default)]
-    proto: Proto,
+    proto: AnyOf<Proto>,
     #[builder(default)]
     af: AddrFamily,
     #[builder(default)]
-    from: Endpoint,
+    from: AnyOf<Endpoint>,
+    #[builder(default, setter(custom))]
+    from_ports: Option<AnyOf<Port>>,
     #[builder(default)]
-    to: Endpoint,
+    to: AnyOf<Endpoint>,
+    #[builder(default, setter(custom))]
+    to_ports: Option<AnyOf<Port>>,
     #[builder(default)]
     tcp_flags: TcpFlags,
     #[builder(default)]
@@ -99,22 +110,167 @@ pub struct FilterRule {
     icmp_type: Option<IcmpType>,
 }
 
+impl FilterRuleBuilder {
+    /// Matches packets on any of the given interfaces, like `on { en0 en1 }` in pf.conf. See
+    /// `FilterRule::expand`.
+    pub fn interface_any_of<T: Into<Interface>>(
+        &mut self,
+        interfaces: impl IntoIterator<Item = T>,
+    ) -> &mut Self {
+        self.interface = Some(interfaces.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Matches packets of any of the given protocols, like `proto { tcp udp }` in pf.conf. See
+    /// `FilterRule::expand`.
+    pub fn proto_any_of(&mut self, protos: impl IntoIterator<Item = Proto>) -> &mut Self {
+        self.proto = Some(protos.into_iter().map(Proto::normalized).collect());
+        self
+    }
+
+    /// Matches packets from any of the given endpoints, like `from { a, b }` in pf.conf. See
+    /// `FilterRule::expand`.
+    pub fn from_any_of<T: Into<Endpoint>>(
+        &mut self,
+        endpoints: impl IntoIterator<Item = T>,
+    ) -> &mut Self {
+        self.from = Some(endpoints.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Matches packets to any of the given endpoints, like `to { a, b }` in pf.conf. See
+    /// `FilterRule::expand`.
+    pub fn to_any_of<T: Into<Endpoint>>(
+        &mut self,
+        endpoints: impl IntoIterator<Item = T>,
+    ) -> &mut Self {
+        self.to = Some(endpoints.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Matches packets from any of the given ports, like `from { a, b } port { 53, 853 }` in
+    /// pf.conf. Every endpoint set with `from` or `from_any_of` is combined with every port, so
+    /// those endpoints must not have ports of their own. See `FilterRule::expand`.
+    pub fn from_port_any_of<T: Into<Port>>(
+        &mut self,
+        ports: impl IntoIterator<Item = T>,
+    ) -> &mut Self {
+        self.from_ports = Some(Some(ports.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Matches packets to any of the given ports, like `to { a, b } port { 53, 853 }` in pf.conf.
+    /// Every endpoint set with `to` or `to_any_of` is combined with every port, so those
+    /// endpoints must not have ports of their own. See `FilterRule::expand`.
+    pub fn to_port_any_of<T: Into<Port>>(
+        &mut self,
+        ports: impl IntoIterator<Item = T>,
+    ) -> &mut Self {
+        self.to_ports = Some(Some(ports.into_iter().map(Into::into).collect()));
+        self
+    }
+}
+
 impl FilterRule {
     /// Returns the `AddrFamily` this rule matches against. Returns an `InvalidRuleCombination`
-    /// error if this rule has an invalid combination of address families.
+    /// error if this rule has an invalid combination of address families, or has lists.
     fn get_af(&self) -> Result<AddrFamily> {
-        let endpoint_af = compatible_af(self.from.get_af(), self.to.get_af())?;
+        let endpoint_af = compatible_af(self.from.single()?.get_af(), self.to.single()?.get_af())?;
         compatible_af(self.af, endpoint_af)
     }
 
+    /// Returns the rules PF needs for this rule, one for every combination of the alternatives
+    /// in its lists, in the same order as pfctl creates them. Combinations with incompatible
+    /// address families, such as an IPv4 source and an IPv6 destination, are skipped.
+    ///
+    /// A rule without lists expands to itself. Fails with an `InvalidRuleCombination` error if
+    /// the rule expands to no rules at all, either because a list is empty or because every
+    /// combination has incompatible address families. In the latter case the error is the one of
+    /// the first combination, so rules without lists fail like before expansion.
+    /// `PfCtl::add_rule` and `Transaction` expand rules on their own.
+    pub fn expand(&self) -> Result<Vec<ExpandedRule>> {
+        let from = endpoints_on_ports(&self.from, self.from_ports.as_ref())?;
+        let to = endpoints_on_ports(&self.to, self.to_ports.as_ref())?;
+        let mut expanded = Vec::new();
+        let mut first_error = None;
+        for rule in self.combinations(&from, &to) {
+            match rule.get_af() {
+                Ok(_) => expanded.push(ExpandedRule(rule)),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if expanded.is_empty() {
+            return Err(first_error.unwrap_or_else(|| {
+                let msg = "Rule has an empty list".to_owned();
+                Error::from(ErrorInternal::InvalidRuleCombination(msg))
+            }));
+        }
+        Ok(expanded)
+    }
+
+    /// Returns a rule without lists for every combination of the alternatives in the lists of
+    /// this rule, nested like pfctl does, with `interface` outermost and `to` innermost. `from`
+    /// and `to` are the endpoints of this rule already combined with its port lists.
+    fn combinations<'a>(
+        &'a self,
+        from: &'a AnyOf<Endpoint>,
+        to: &'a AnyOf<Endpoint>,
+    ) -> impl Iterator<Item = FilterRule> + 'a {
+        self.interface.iter().flat_map(move |interface| {
+            self.proto.iter().flat_map(move |&proto| {
+                from.iter().flat_map(move |&from| {
+                    to.iter().map(move |&to| FilterRule {
+                        interface: AnyOf::from(interface.clone()),
+                        proto: AnyOf::from(proto),
+                        from: AnyOf::from(from),
+                        from_ports: None,
+                        to: AnyOf::from(to),
+                        to_ports: None,
+                        ..self.clone()
+                    })
+                })
+            })
+        })
+    }
+
     /// Accessor for `route`
     pub fn get_route(&self) -> &Route {
         &self.route
     }
 
+    /// Replaces the pool address of `route`, used when reading rules back from PF since the
+    /// address pool is not part of `pf_rule`.
+    pub(crate) fn set_route_pool_addr(&mut self, pool_addr: PoolAddr) {
+        self.route = self.route.clone().with_pool_addr(pool_addr);
+    }
+
+    /// Returns this rule as PF would return it when read back, with implicit fields such as the
+    /// address family filled in.
+    pub(crate) fn normalized(&self) -> Result<Self> {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        self.try_copy_to(&mut pf_rule)?;
+        let mut rule = FilterRule::try_from(pf_rule)?;
+        if let Some(pool_addr) = self.route.get_pool_addr() {
+            rule.set_route_pool_addr(pool_addr.clone());
+        }
+        Ok(rule)
+    }
+
+    /// Returns the fingerprint of this rule. See `RuleFingerprint`.
+    ///
+    /// Fails for rules with lists, since PF stores them as several rules. Fingerprint each of the
+    /// rules returned by `expand` instead.
+    pub fn fingerprint(&self) -> Result<RuleFingerprint> {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        self.try_copy_to(&mut pf_rule)?;
+        RuleFingerprint::new(&pf_rule, self.route.get_pool_addr())
+    }
+
     /// Validates the combination of StatePolicy and Proto.
     fn validate_state_policy(&self) -> Result<StatePolicy> {
-        match (self.keep_state, self.proto) {
+        match (self.keep_state, self.proto.single()?.normalized()) {
             (StatePolicy::None, _)
             | (StatePolicy::Keep, _)
             | (StatePolicy::Modulate, Proto::Tcp)
@@ -142,12 +298,14 @@ impl TryCopyTo<ffi::pfvar::pf_rule> for FilterRule {
         pf_rule.flagset = (&self.tcp_flags.mask).into();
         pf_rule.rule_flag = self.action.rule_flags();
 
-        self.interface.try_copy_to(&mut pf_rule.ifname)?;
-        pf_rule.proto = self.proto.into();
+        self.interface.single()?.try_copy_to_rule(pf_rule)?;
+        pf_rule.proto = (*self.proto.single()?).into();
         pf_rule.af = self.get_af()?.into();
 
-        self.from.try_copy_to(&mut pf_rule.src)?;
-        self.to.try_copy_to(&mut pf_rule.dst)?;
+        let from = endpoints_on_ports(&self.from, self.from_ports.as_ref())?;
+        from.single()?.try_copy_to(&mut pf_rule.src)?;
+        let to = endpoints_on_ports(&self.to, self.to_ports.as_ref())?;
+        to.single()?.try_copy_to(&mut pf_rule.dst)?;
         self.label
             .try_copy_to(&mut pf_rule.label)
             .map_err(ErrorInternal::InvalidLabel)?;
@@ -161,6 +319,81 @@ impl TryCopyTo<ffi::pfvar::pf_rule> for FilterRule {
     }
 }
 
+/// Reads a filter rule back from PF. Since the address pool of a rule is not part of `pf_rule`,
+/// routes are returned with `Ip::Any` as pool address. `PfCtl::get_filter_rules` fills in the
+/// real pool address.
+impl TryFrom<ffi::pfvar::pf_rule> for FilterRule {
+    type Error = crate::Error;
+
+    fn try_from(pf_rule: ffi::pfvar::pf_rule) -> Result<Self> {
+        let af = AddrFamily::try_from(pf_rule.af)?;
+        Ok(FilterRule {
+            action: FilterRuleAction::try_from_raw(pf_rule.action, pf_rule.rule_flag)?,
+            direction: Direction::try_from(pf_rule.direction)?,
+            quick: pf_rule.quick != 0,
+            log: RuleLogSet::from(pf_rule.log),
+            route: Route::try_from_raw(pf_rule.rt)?,
+            keep_state: StatePolicy::try_from(pf_rule.keep_state)?,
+            interface: AnyOf::from(Interface::try_from_rule(&pf_rule)?),
+            proto: AnyOf::from(Proto::from(pf_rule.proto)),
+            af,
+            from: AnyOf::from(Endpoint::try_from_raw(&pf_rule.src, af)?),
+            from_ports: None,
+            to: AnyOf::from(Endpoint::try_from_raw(&pf_rule.dst, af)?),
+            to_ports: None,
+            tcp_flags: TcpFlags {
+                check: TcpFlagSet::from(pf_rule.flags),
+                mask: TcpFlagSet::from(pf_rule.flagset),
+            },
+            label: crate::cstr_to_string(&pf_rule.label).map_err(ErrorInternal::InvalidLabel)?,
+            user: Uid::try_from(pf_rule.uid)?,
+            group: Gid::try_from(pf_rule.gid)?,
+            icmp_type: IcmpType::try_from_raw(pf_rule.type_, pf_rule.code)?,
+        })
+    }
+}
+
+/// Returns every endpoint in `endpoints` on every port in `ports`, with the ports innermost like
+/// pfctl, or `endpoints` unchanged if there is no port list. Fails if an endpoint has a port of
+/// its own in addition to the port list.
+fn endpoints_on_ports(
+    endpoints: &AnyOf<Endpoint>,
+    ports: Option<&AnyOf<Port>>,
+) -> Result<AnyOf<Endpoint>> {
+    let Some(ports) = ports else {
+        return Ok(endpoints.clone());
+    };
+    if endpoints
+        .iter()
+        .any(|endpoint| endpoint.port() != Port::Any)
+    {
+        let msg = "Endpoints can not have ports together with a port list".to_owned();
+        return Err(Error::from(ErrorInternal::InvalidRuleCombination(msg)));
+    }
+    Ok(endpoints
+        .iter()
+        .flat_map(|endpoint| ports.iter().map(|&port| endpoint.with_port(port)))
+        .collect())
+}
+
+/// A filter rule without lists, as PF stores it. Returned by `FilterRule::expand`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExpandedRule(FilterRule);
+
+impl Deref for ExpandedRule {
+    type Target = FilterRule;
+
+    fn deref(&self) -> &FilterRule {
+        &self.0
+    }
+}
+
+impl From<ExpandedRule> for FilterRule {
+    fn from(rule: ExpandedRule) -> Self {
+        rule.0
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, derive_builder::Builder)]
 #[builder(setter(into))]
 #[builder(build_fn(error = "Error"))]
@@ -196,6 +429,43 @@ impl NatRule {
             NatRuleAction::NoNat => None,
         }
     }
+
+    /// Replaces the IP of `nat_to`, used when reading rules back from PF since the address pool
+    /// is not part of `pf_rule`.
+    pub(crate) fn set_nat_to_ip(&mut self, ip: Ip) {
+        if let NatRuleAction::Nat { nat_to } = &mut self.action {
+            *nat_to = NatEndpoint(Endpoint::new(ip, nat_to.port()));
+        }
+    }
+
+    /// Returns this rule as PF would return it when read back, with implicit fields such as the
+    /// address family filled in.
+    pub(crate) fn normalized(&self) -> Result<Self> {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        self.try_copy_to(&mut pf_rule)?;
+        if let Some(nat_to) = self.get_nat_to() {
+            nat_to.port().try_copy_to(&mut pf_rule.rpool)?;
+        }
+        let mut rule = NatRule::try_from(pf_rule)?;
+        if let Some(nat_to) = self.get_nat_to() {
+            rule.set_nat_to_ip(nat_to.ip());
+        }
+        Ok(rule)
+    }
+
+    /// Returns the fingerprint of this rule. See `RuleFingerprint`.
+    pub fn fingerprint(&self) -> Result<RuleFingerprint> {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        self.try_copy_to(&mut pf_rule)?;
+        let pool_addr = match self.get_nat_to() {
+            Some(nat_to) => {
+                nat_to.port().try_copy_to(&mut pf_rule.rpool)?;
+                Some(PoolAddr::from(nat_to.ip()))
+            }
+            None => None,
+        };
+        RuleFingerprint::new(&pf_rule, pool_addr.as_ref())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -255,8 +525,11 @@ impl TryCopyTo<ffi::pfvar::pf_rule> for NatRule {
 
     fn try_copy_to(&self, pf_rule: &mut ffi::pfvar::pf_rule) -> Result<()> {
         pf_rule.action = self.action.into();
-        self.interface.try_copy_to(&mut pf_rule.ifname)?;
+        self.interface.try_copy_to_rule(pf_rule)?;
         pf_rule.af = self.get_af()?.into();
+        if let Some(nat_to) = self.get_nat_to() {
+            validate_translation_target(&nat_to)?;
+        }
 
         self.from.try_copy_to(&mut pf_rule.src)?;
         self.to.try_copy_to(&mut pf_rule.dst)?;
@@ -265,6 +538,33 @@ impl TryCopyTo<ffi::pfvar::pf_rule> for NatRule {
     }
 }
 
+/// Reads a NAT rule back from PF. Since the address pool of a rule is not part of `pf_rule`,
+/// `nat_to` is returned with `Ip::Any` as address. `PfCtl::get_nat_rules` fills in the real one.
+impl TryFrom<ffi::pfvar::pf_rule> for NatRule {
+    type Error = crate::Error;
+
+    fn try_from(pf_rule: ffi::pfvar::pf_rule) -> Result<Self> {
+        let af = AddrFamily::try_from(pf_rule.af)?;
+        let action = match u32::from(pf_rule.action) {
+            v if v == ffi::pfvar::PF_NAT as u32 => NatRuleAction::Nat {
+                nat_to: NatEndpoint(Endpoint::new(Ip::Any, Port::try_from(pf_rule.rpool)?)),
+            },
+            v if v == ffi::pfvar::PF_NONAT as u32 => NatRuleAction::NoNat,
+            _ => {
+                let msg = format!("{} is not a NAT rule action", pf_rule.action);
+                return Err(Error::from(ErrorInternal::UnsupportedRule(msg)));
+            }
+        };
+        Ok(NatRule {
+            action,
+            interface: Interface::try_from_rule(&pf_rule)?,
+            af,
+            from: Endpoint::try_from_raw(&pf_rule.src, af)?,
+            to: Endpoint::try_from_raw(&pf_rule.dst, af)?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, derive_builder::Builder)]
 #[builder(setter(into))]
 #[builder(build_fn(error = "Error"))]
@@ -278,7 +578,7 @@ pub struct RedirectRule {
     log: RuleLogSet,
     #[builder(default)]
     interface: Interface,
-    #[builder(default)]
+    #[builder(default, setter(custom))]
     proto: Proto,
     #[builder(default)]
     af: AddrFamily,
@@ -295,6 +595,15 @@ pub struct RedirectRule {
     redirect_to: Endpoint,
 }
 
+impl RedirectRuleBuilder {
+    /// Matches packets of the given protocol. `Proto::Other` holding the number of a protocol
+    /// with a variant of its own is stored as that variant.
+    pub fn proto<T: Into<Proto>>(&mut self, proto: T) -> &mut Self {
+        self.proto = Some(proto.into().normalized());
+        self
+    }
+}
+
 impl RedirectRule {
     /// Returns the `AddrFamily` this rule matches against. Returns an `InvalidRuleCombination`
     /// error if this rule has an invalid combination of address families.
@@ -308,6 +617,31 @@ impl RedirectRule {
     pub fn get_redirect_to(&self) -> Endpoint {
         self.redirect_to
     }
+
+    /// Replaces the IP of `redirect_to`, used when reading rules back from PF since the address
+    /// pool is not part of `pf_rule`.
+    pub(crate) fn set_redirect_to_ip(&mut self, ip: Ip) {
+        self.redirect_to = Endpoint::new(ip, self.redirect_to.port());
+    }
+
+    /// Returns this rule as PF would return it when read back, with implicit fields such as the
+    /// address family filled in.
+    pub(crate) fn normalized(&self) -> Result<Self> {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        self.try_copy_to(&mut pf_rule)?;
+        self.redirect_to.port().try_copy_to(&mut pf_rule.rpool)?;
+        let mut rule = RedirectRule::try_from(pf_rule)?;
+        rule.set_redirect_to_ip(self.redirect_to.ip());
+        Ok(rule)
+    }
+
+    /// Returns the fingerprint of this rule. See `RuleFingerprint`.
+    pub fn fingerprint(&self) -> Result<RuleFingerprint> {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        self.try_copy_to(&mut pf_rule)?;
+        self.redirect_to.port().try_copy_to(&mut pf_rule.rpool)?;
+        RuleFingerprint::new(&pf_rule, Some(&PoolAddr::from(self.redirect_to.ip())))
+    }
 }
 
 impl TryCopyTo<ffi::pfvar::pf_rule> for RedirectRule {
@@ -318,9 +652,10 @@ impl TryCopyTo<ffi::pfvar::pf_rule> for RedirectRule {
         pf_rule.direction = self.direction.into();
         pf_rule.quick = self.quick as u8;
         pf_rule.log = (&self.log).into();
-        self.interface.try_copy_to(&mut pf_rule.ifname)?;
+        self.interface.try_copy_to_rule(pf_rule)?;
         pf_rule.proto = self.proto.into();
         pf_rule.af = self.get_af()?.into();
+        validate_translation_target(&self.redirect_to)?;
 
         self.from.try_copy_to(&mut pf_rule.src)?;
         self.to.try_copy_to(&mut pf_rule.dst)?;
@@ -334,6 +669,32 @@ impl TryCopyTo<ffi::pfvar::pf_rule> for RedirectRule {
     }
 }
 
+/// Reads a redirect rule back from PF. Since the address pool of a rule is not part of
+/// `pf_rule`, `redirect_to` is returned with `Ip::Any` as address.
+/// `PfCtl::get_redirect_rules` fills in the real one.
+impl TryFrom<ffi::pfvar::pf_rule> for RedirectRule {
+    type Error = crate::Error;
+
+    fn try_from(pf_rule: ffi::pfvar::pf_rule) -> Result<Self> {
+        let af = AddrFamily::try_from(pf_rule.af)?;
+        Ok(RedirectRule {
+            action: RedirectRuleAction::try_from(pf_rule.action)?,
+            direction: Direction::try_from(pf_rule.direction)?,
+            quick: pf_rule.quick != 0,
+            log: RuleLogSet::from(pf_rule.log),
+            interface: Interface::try_from_rule(&pf_rule)?,
+            proto: Proto::from(pf_rule.proto),
+            af,
+            from: Endpoint::try_from_raw(&pf_rule.src, af)?,
+            to: Endpoint::try_from_raw(&pf_rule.dst, af)?,
+            label: crate::cstr_to_string(&pf_rule.label).map_err(ErrorInternal::InvalidLabel)?,
+            user: Uid::try_from(pf_rule.uid)?,
+            group: Gid::try_from(pf_rule.gid)?,
+            redirect_to: Endpoint::new(Ip::Any, Port::try_from(pf_rule.rpool)?),
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, derive_builder::Builder)]
 #[builder(setter(into))]
 #[builder(build_fn(error = "Error"))]
@@ -343,6 +704,22 @@ pub struct ScrubRule {
     direction: Direction,
 }
 
+impl ScrubRule {
+    /// Returns this rule as PF would return it when read back.
+    pub(crate) fn normalized(&self) -> Result<Self> {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        self.try_copy_to(&mut pf_rule)?;
+        ScrubRule::try_from(pf_rule)
+    }
+
+    /// Returns the fingerprint of this rule. See `RuleFingerprint`.
+    pub fn fingerprint(&self) -> Result<RuleFingerprint> {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        self.try_copy_to(&mut pf_rule)?;
+        RuleFingerprint::new(&pf_rule, None)
+    }
+}
+
 impl TryCopyTo<ffi::pfvar::pf_rule> for ScrubRule {
     type Error = crate::Error;
 
@@ -353,6 +730,72 @@ impl TryCopyTo<ffi::pfvar::pf_rule> for ScrubRule {
     }
 }
 
+impl TryFrom<ffi::pfvar::pf_rule> for ScrubRule {
+    type Error = crate::Error;
+
+    fn try_from(pf_rule: ffi::pfvar::pf_rule) -> Result<Self> {
+        Ok(ScrubRule {
+            action: ScrubRuleAction::try_from(pf_rule.action)?,
+            direction: Direction::try_from(pf_rule.direction)?,
+        })
+    }
+}
+
+/// Any of the rule types PF supports, for handling rules of all kinds the same way.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Rule {
+    Filter(FilterRule),
+    Nat(NatRule),
+    Redirect(RedirectRule),
+    Scrub(ScrubRule),
+}
+
+impl Rule {
+    /// Returns the kind of ruleset this rule belongs to.
+    pub fn kind(&self) -> RulesetKind {
+        match self {
+            Rule::Filter(_) => RulesetKind::Filter,
+            Rule::Nat(_) => RulesetKind::Nat,
+            Rule::Redirect(_) => RulesetKind::Redirect,
+            Rule::Scrub(_) => RulesetKind::Scrub,
+        }
+    }
+
+    /// Returns the fingerprint of this rule. See `RuleFingerprint`.
+    pub fn fingerprint(&self) -> Result<RuleFingerprint> {
+        match self {
+            Rule::Filter(rule) => rule.fingerprint(),
+            Rule::Nat(rule) => rule.fingerprint(),
+            Rule::Redirect(rule) => rule.fingerprint(),
+            Rule::Scrub(rule) => rule.fingerprint(),
+        }
+    }
+}
+
+impl From<FilterRule> for Rule {
+    fn from(rule: FilterRule) -> Self {
+        Rule::Filter(rule)
+    }
+}
+
+impl From<NatRule> for Rule {
+    fn from(rule: NatRule) -> Self {
+        Rule::Nat(rule)
+    }
+}
+
+impl From<RedirectRule> for Rule {
+    fn from(rule: RedirectRule) -> Self {
+        Rule::Redirect(rule)
+    }
+}
+
+impl From<ScrubRule> for Rule {
+    fn from(rule: ScrubRule) -> Self {
+        Rule::Scrub(rule)
+    }
+}
+
 fn compatible_af(af1: AddrFamily, af2: AddrFamily) -> Result<AddrFamily> {
     match (af1, af2) {
         (af1, af2) if af1 == af2 => Ok(af1),
@@ -365,6 +808,22 @@ fn compatible_af(af1: AddrFamily, af2: AddrFamily) -> Result<AddrFamily> {
     }
 }
 
+/// Packets can only be translated to an address, so a negated endpoint, an address range or a
+/// route check can not be the target of a NAT or redirect rule.
+fn validate_translation_target(endpoint: &Endpoint) -> Result<()> {
+    if endpoint.is_negated() {
+        let msg = "A negated address can not be a translation target".to_owned();
+        return Err(Error::from(ErrorInternal::InvalidRuleCombination(msg)));
+    }
+    match endpoint.ip() {
+        ip @ (Ip::Range(..) | Ip::NoRoute | Ip::UrpfFailed) => {
+            let msg = format!("{ip:?} can not be a translation target");
+            Err(Error::from(ErrorInternal::InvalidRuleCombination(msg)))
+        }
+        _ => Ok(()),
+    }
+}
+
 // Implementations to convert types that are not ours into their FFI representation
 
 impl CopyTo<ffi::pfvar::pf_addr_wrap> for IpNetwork {
@@ -426,6 +885,8 @@ impl<T: AsRef<str>> TryCopyTo<[i8]> for T {
 #[cfg(test)]
 mod filter_rule_tests {
     use super::*;
+    use crate::TableName;
+    use zerocopy::FromZeros;
 
     #[test]
     fn correct_af_default() {
@@ -522,6 +983,654 @@ mod filter_rule_tests {
         );
     }
 
+    #[test]
+    fn read_back_rule() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Drop(DropAction::ReturnIcmp))
+            .direction(Direction::In)
+            .quick(true)
+            .log(RuleLogSet::new(&[
+                RuleLog::IncludeMatchingState,
+                RuleLog::SocketOwner,
+            ]))
+            .keep_state(StatePolicy::Keep)
+            .interface("utun0")
+            .proto(Proto::Icmp)
+            .af(AddrFamily::Ipv4)
+            .from(Ip::from(
+                IpNetwork::new(Ipv4Addr::new(10, 0, 0, 0).into(), 8).unwrap(),
+            ))
+            .to(Endpoint::new(
+                Ipv4Addr::new(192, 168, 0, 1),
+                Port::Range(1000, 2000, PortRangeModifier::Except),
+            ))
+            .label("read-back")
+            .user(Id::Range(500, 600, IdRangeModifier::Inclusive))
+            .group(Id::One(20, IdUnaryModifier::NotEqual))
+            .icmp_type(IcmpType::Unreach(IcmpUnreachCode::PortUnreach))
+            .build()
+            .unwrap();
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(FilterRule::try_from(pf_rule).unwrap(), rule);
+    }
+
+    #[test]
+    fn read_back_ipv6_rule() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .proto(Proto::Tcp)
+            .af(AddrFamily::Ipv6)
+            .to(Endpoint::new(
+                Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+                Port::One(443, PortUnaryModifier::Equal),
+            ))
+            .tcp_flags(([TcpFlag::Syn], [TcpFlag::Syn, TcpFlag::Ack]))
+            .build()
+            .unwrap();
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(FilterRule::try_from(pf_rule).unwrap(), rule);
+    }
+
+    #[test]
+    fn read_back_table_rule() {
+        let relays = TableName::new("relays").unwrap();
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .direction(Direction::Out)
+            .to(Endpoint::new(
+                relays,
+                Port::One(51820, PortUnaryModifier::Equal),
+            ))
+            .build()
+            .unwrap();
+        assert_eq!(rule.get_af().unwrap(), AddrFamily::Any);
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(pf_rule.dst.addr.type_, ffi::pfvar::PF_ADDR_TABLE as u8);
+        assert_eq!(FilterRule::try_from(pf_rule).unwrap(), rule);
+        assert_eq!(
+            FilterRule::try_from(pf_rule).unwrap().to[0].ip(),
+            Ip::Table(relays)
+        );
+    }
+
+    #[test]
+    fn af_table_is_compatible_with_any_family() {
+        let relays = TableName::new("relays").unwrap();
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .from(Ipv6Addr::LOCALHOST)
+            .to(relays)
+            .build()
+            .unwrap();
+        assert_eq!(rule.get_af().unwrap(), AddrFamily::Ipv6);
+    }
+
+    #[test]
+    fn read_back_interface_rule() {
+        for modifier in [
+            InterfaceAddrModifier::Address,
+            InterfaceAddrModifier::Network,
+            InterfaceAddrModifier::Broadcast,
+            InterfaceAddrModifier::Peer,
+            InterfaceAddrModifier::NoAlias,
+        ] {
+            let rule = FilterRuleBuilder::default()
+                .action(FilterRuleAction::Pass)
+                .af(AddrFamily::Ipv4)
+                .from(Ip::interface("utun3", modifier).unwrap())
+                .build()
+                .unwrap();
+
+            let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+            rule.try_copy_to(&mut pf_rule).unwrap();
+            assert_eq!(pf_rule.src.addr.type_, ffi::pfvar::PF_ADDR_DYNIFTL as u8);
+            assert_eq!(FilterRule::try_from(pf_rule).unwrap(), rule);
+        }
+    }
+
+    #[test]
+    fn read_back_unsupported_interface_flags() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .from(Ip::interface("en0", InterfaceAddrModifier::Network).unwrap())
+            .build()
+            .unwrap();
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        pf_rule.src.addr.iflags |= ffi::pfvar::PFI_AFLAG_NOALIAS as u8;
+        assert_eq!(
+            FilterRule::try_from(pf_rule).unwrap_err().kind(),
+            crate::ErrorKind::UnsupportedRule
+        );
+    }
+
+    #[test]
+    fn interface_addr_name_limits() {
+        assert!(InterfaceAddrName::new("bridge100").is_ok());
+        assert!(InterfaceAddrName::new("a".repeat(15).as_str()).is_ok());
+        for name in ["", "a-very-long-name", "en\0"] {
+            assert_eq!(
+                InterfaceAddrName::new(name).unwrap_err().kind(),
+                crate::ErrorKind::InvalidInterfaceName
+            );
+        }
+    }
+
+    #[test]
+    fn read_back_negated_endpoint_rule() {
+        let lan =
+            IpNetwork::V4(ipnetwork::Ipv4Network::new(Ipv4Addr::new(10, 0, 0, 0), 8).unwrap());
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .direction(Direction::Out)
+            .af(AddrFamily::Ipv4)
+            .to(Endpoint::not(lan, Port::Any))
+            .build()
+            .unwrap();
+        assert_eq!(rule.get_af().unwrap(), AddrFamily::Ipv4);
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(pf_rule.src.neg, 0);
+        assert_eq!(pf_rule.dst.neg, 1);
+        let read_back = FilterRule::try_from(pf_rule).unwrap();
+        assert!(read_back.to[0].is_negated());
+        assert_eq!(read_back, rule);
+    }
+
+    #[test]
+    fn negated_endpoint_keeps_family() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .from(Endpoint::not(Ipv4Addr::LOCALHOST, Port::Any))
+            .to(Ipv6Addr::LOCALHOST)
+            .build()
+            .unwrap();
+        assert_eq!(
+            rule.get_af().unwrap_err().kind(),
+            crate::ErrorKind::InvalidRuleCombination
+        );
+    }
+
+    #[test]
+    fn negated_redirect_target_is_invalid() {
+        let rule = RedirectRuleBuilder::default()
+            .action(RedirectRuleAction::Redirect)
+            .redirect_to(Endpoint::not(Ipv4Addr::LOCALHOST, 4000))
+            .build()
+            .unwrap();
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        assert_eq!(
+            rule.try_copy_to(&mut pf_rule).unwrap_err().kind(),
+            crate::ErrorKind::InvalidRuleCombination
+        );
+    }
+
+    #[test]
+    fn read_back_range_rule() {
+        let start = IpAddr::from(Ipv4Addr::new(10, 0, 0, 10));
+        let end = IpAddr::from(Ipv4Addr::new(10, 0, 0, 50));
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .af(AddrFamily::Ipv4)
+            .from(Ip::Range(start, end))
+            .build()
+            .unwrap();
+        assert_eq!(rule.get_af().unwrap(), AddrFamily::Ipv4);
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(pf_rule.src.addr.type_, ffi::pfvar::PF_ADDR_RANGE as u8);
+        assert_eq!(FilterRule::try_from(pf_rule).unwrap(), rule);
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        let v4 = IpAddr::from(Ipv4Addr::new(10, 0, 0, 10));
+        let v4_before = IpAddr::from(Ipv4Addr::new(10, 0, 0, 1));
+        let v6 = IpAddr::from(Ipv6Addr::LOCALHOST);
+        for range in [
+            Ip::Range(v4, v6),
+            Ip::Range(v6, v4),
+            Ip::Range(v4, v4_before),
+        ] {
+            let rule = FilterRuleBuilder::default()
+                .action(FilterRuleAction::Pass)
+                .to(range)
+                .build()
+                .unwrap();
+            let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+            assert_eq!(
+                rule.try_copy_to(&mut pf_rule).unwrap_err().kind(),
+                crate::ErrorKind::InvalidRuleCombination
+            );
+        }
+        assert!(Ip::Range(v4, v4).validate().is_ok());
+    }
+
+    #[test]
+    fn read_back_route_check_rules() {
+        for (ip, addr_type) in [
+            (Ip::NoRoute, ffi::pfvar::PF_ADDR_NOROUTE),
+            (Ip::UrpfFailed, ffi::pfvar::PF_ADDR_URPFFAILED),
+        ] {
+            let rule = FilterRuleBuilder::default()
+                .action(FilterRuleAction::Drop(DropAction::Drop))
+                .direction(Direction::In)
+                .from(ip)
+                .build()
+                .unwrap();
+            assert_eq!(rule.get_af().unwrap(), AddrFamily::Any);
+
+            let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+            rule.try_copy_to(&mut pf_rule).unwrap();
+            assert_eq!(pf_rule.src.addr.type_, addr_type as u8);
+            assert_eq!(FilterRule::try_from(pf_rule).unwrap(), rule);
+        }
+    }
+
+    #[test]
+    fn route_check_redirect_target_is_invalid() {
+        let rule = RedirectRuleBuilder::default()
+            .action(RedirectRuleAction::Redirect)
+            .redirect_to(Ip::NoRoute)
+            .build()
+            .unwrap();
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        assert_eq!(
+            rule.try_copy_to(&mut pf_rule).unwrap_err().kind(),
+            crate::ErrorKind::InvalidRuleCombination
+        );
+    }
+
+    #[test]
+    fn read_back_negated_interface_rules() {
+        let filter_rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Drop(DropAction::Drop))
+            .interface(Interface::not("utun3"))
+            .build()
+            .unwrap();
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        filter_rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(pf_rule.ifnot, 1);
+        assert_eq!(FilterRule::try_from(pf_rule).unwrap(), filter_rule);
+
+        let nat_rule = NatRuleBuilder::default()
+            .action(NatRuleAction::NoNat)
+            .interface(Interface::not("utun3"))
+            .build()
+            .unwrap();
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        nat_rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(pf_rule.ifnot, 1);
+        assert_eq!(NatRule::try_from(pf_rule).unwrap(), nat_rule);
+
+        let redirect_rule = RedirectRuleBuilder::default()
+            .action(RedirectRuleAction::NoRedirect)
+            .interface(Interface::not("utun3"))
+            .redirect_to(Ip::Any)
+            .build()
+            .unwrap();
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        redirect_rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(pf_rule.ifnot, 1);
+        assert_eq!(RedirectRule::try_from(pf_rule).unwrap(), redirect_rule);
+    }
+
+    #[test]
+    fn negated_interface_outside_rule_is_invalid() {
+        let mut ifname = [0; IFNAMSIZ];
+        assert_eq!(
+            Interface::not("utun3")
+                .try_copy_to(&mut ifname[..])
+                .unwrap_err()
+                .kind(),
+            crate::ErrorKind::InvalidInterfaceName
+        );
+    }
+
+    #[test]
+    fn expand_lists_like_pfctl() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .proto_any_of([Proto::Tcp, Proto::Udp])
+            .to_any_of([
+                Ip::from(Ipv4Addr::new(10, 0, 0, 1)),
+                Ip::from(Ipv6Addr::LOCALHOST),
+            ])
+            .build()
+            .unwrap();
+        let expanded: Vec<(Proto, AddrFamily)> = rule
+            .expand()
+            .unwrap()
+            .iter()
+            .map(|rule| (*rule.proto.single().unwrap(), rule.get_af().unwrap()))
+            .collect();
+        assert_eq!(
+            expanded,
+            [
+                (Proto::Tcp, AddrFamily::Ipv4),
+                (Proto::Tcp, AddrFamily::Ipv6),
+                (Proto::Udp, AddrFamily::Ipv4),
+                (Proto::Udp, AddrFamily::Ipv6),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_port_lists_like_pfctl() {
+        let a = Ipv4Addr::new(10, 0, 0, 1);
+        let b = Ipv4Addr::new(10, 0, 0, 2);
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .proto_any_of([Proto::Tcp, Proto::Udp])
+            .to_any_of([a, b])
+            .to_port_any_of([53, 853])
+            .build()
+            .unwrap();
+        let expanded: Vec<(Proto, Endpoint)> = rule
+            .expand()
+            .unwrap()
+            .iter()
+            .map(|rule| (*rule.proto.single().unwrap(), *rule.to.single().unwrap()))
+            .collect();
+        assert_eq!(
+            expanded,
+            [
+                (Proto::Tcp, Endpoint::new(a, 53)),
+                (Proto::Tcp, Endpoint::new(a, 853)),
+                (Proto::Tcp, Endpoint::new(b, 53)),
+                (Proto::Tcp, Endpoint::new(b, 853)),
+                (Proto::Udp, Endpoint::new(a, 53)),
+                (Proto::Udp, Endpoint::new(a, 853)),
+                (Proto::Udp, Endpoint::new(b, 53)),
+                (Proto::Udp, Endpoint::new(b, 853)),
+            ]
+        );
+        assert!(
+            expanded
+                .iter()
+                .all(|(_, to)| to.get_af() == AddrFamily::Ipv4)
+        );
+    }
+
+    #[test]
+    fn single_port_list_needs_no_expansion() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .proto(Proto::Udp)
+            .from_port_any_of([Port::Range(1024, 65535, PortRangeModifier::Inclusive)])
+            .to(Ipv4Addr::new(10, 0, 0, 1))
+            .to_port_any_of([53])
+            .build()
+            .unwrap();
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        let read_back = FilterRule::try_from(pf_rule).unwrap();
+        assert_eq!(
+            read_back.from[..],
+            [Endpoint::from(Port::Range(
+                1024,
+                65535,
+                PortRangeModifier::Inclusive
+            ))]
+        );
+        assert_eq!(
+            read_back.to[..],
+            [Endpoint::new(Ipv4Addr::new(10, 0, 0, 1), 53)]
+        );
+    }
+
+    #[test]
+    fn port_list_conflicts_with_endpoint_port() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .proto(Proto::Tcp)
+            .to(Endpoint::new(Ipv4Addr::new(10, 0, 0, 1), 443))
+            .to_port_any_of([80, 8080])
+            .build()
+            .unwrap();
+        assert_eq!(
+            rule.expand().unwrap_err().to_string(),
+            "Invalid rule combination: Endpoints can not have ports together with a port list"
+        );
+    }
+
+    #[test]
+    fn expand_skips_incompatible_families() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .interface_any_of(["en0", "en1"])
+            .from_any_of([Ipv4Addr::new(10, 0, 0, 1)])
+            .to_any_of([
+                Ip::from(Ipv4Addr::new(10, 0, 0, 2)),
+                Ip::from(Ipv6Addr::LOCALHOST),
+            ])
+            .build()
+            .unwrap();
+        let expanded = rule.expand().unwrap();
+        assert_eq!(expanded.len(), 2);
+        assert_eq!(expanded[0].interface[..], [Interface::from("en0")]);
+        assert_eq!(expanded[1].interface[..], [Interface::from("en1")]);
+        assert!(
+            expanded
+                .iter()
+                .all(|rule| rule.to[0].get_af() == AddrFamily::Ipv4)
+        );
+    }
+
+    #[test]
+    fn expand_without_lists() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .to(Ipv4Addr::new(10, 0, 0, 1))
+            .build()
+            .unwrap();
+        let mut expanded = rule.expand().unwrap();
+        assert_eq!(expanded.len(), 1);
+        assert_eq!(FilterRule::from(expanded.remove(0)), rule);
+
+        let incompatible = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .from(Ipv4Addr::new(10, 0, 0, 1))
+            .to(Ipv6Addr::LOCALHOST)
+            .build()
+            .unwrap();
+        assert_eq!(
+            incompatible.expand().unwrap_err().to_string(),
+            "Invalid rule combination: AddrFamily IPv4 and IPv6 are incompatible"
+        );
+    }
+
+    #[test]
+    fn unexpanded_rule_can_not_be_copied() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .proto_any_of([Proto::Tcp, Proto::Udp])
+            .build()
+            .unwrap();
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        assert_eq!(
+            rule.try_copy_to(&mut pf_rule).unwrap_err().kind(),
+            crate::ErrorKind::InvalidRuleCombination
+        );
+        assert!(rule.fingerprint().is_err());
+        assert!(
+            rule.expand()
+                .unwrap()
+                .iter()
+                .all(|rule| rule.fingerprint().is_ok())
+        );
+
+        let empty = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .to_any_of(Vec::<Endpoint>::new())
+            .build()
+            .unwrap();
+        assert!(empty.expand().is_err());
+    }
+
+    #[test]
+    fn read_back_unsupported_action() {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        pf_rule.action = ffi::pfvar::PF_NAT as u8;
+        assert_eq!(
+            FilterRule::try_from(pf_rule).unwrap_err().kind(),
+            crate::ErrorKind::UnsupportedRule
+        );
+    }
+
+    #[test]
+    fn read_back_nat_rule() {
+        let rule = NatRuleBuilder::default()
+            .action(NatRuleAction::Nat {
+                nat_to: NatEndpoint::from(Endpoint::new(
+                    Ipv4Addr::new(10, 0, 0, 1),
+                    Port::Range(32768, 49151, PortRangeModifier::Inclusive),
+                )),
+            })
+            .interface("en0")
+            .af(AddrFamily::Ipv4)
+            .from(Ip::from(
+                IpNetwork::new(Ipv4Addr::new(192, 168, 0, 0).into(), 16).unwrap(),
+            ))
+            .build()
+            .unwrap();
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        let nat_to = rule.get_nat_to().unwrap();
+        nat_to.port().try_copy_to(&mut pf_rule.rpool).unwrap();
+
+        let mut read_back = NatRule::try_from(pf_rule).unwrap();
+        assert_eq!(read_back.get_nat_to().unwrap().ip(), Ip::Any);
+        read_back.set_nat_to_ip(nat_to.ip());
+        assert_eq!(read_back, rule);
+    }
+
+    #[test]
+    fn read_back_nonat_rule() {
+        let rule = NatRuleBuilder::default()
+            .action(NatRuleAction::NoNat)
+            .af(AddrFamily::Ipv6)
+            .to(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))
+            .build()
+            .unwrap();
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(NatRule::try_from(pf_rule).unwrap(), rule);
+    }
+
+    #[test]
+    fn read_back_redirect_rule() {
+        let rule = RedirectRuleBuilder::default()
+            .action(RedirectRuleAction::Redirect)
+            .direction(Direction::In)
+            .interface("lo0")
+            .proto(Proto::Tcp)
+            .af(AddrFamily::Ipv4)
+            .to(Endpoint::new(Ipv4Addr::new(127, 0, 0, 1), 3000))
+            .label("read-back")
+            .redirect_to(Endpoint::new(Ipv4Addr::new(127, 0, 0, 1), 4000))
+            .build()
+            .unwrap();
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        let redirect_to = rule.get_redirect_to();
+        redirect_to.port().try_copy_to(&mut pf_rule.rpool).unwrap();
+
+        let mut read_back = RedirectRule::try_from(pf_rule).unwrap();
+        assert_eq!(read_back.get_redirect_to().ip(), Ip::Any);
+        read_back.set_redirect_to_ip(redirect_to.ip());
+        assert_eq!(read_back, rule);
+    }
+
+    #[test]
+    fn read_back_nat_rule_unsupported_action() {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        pf_rule.action = ffi::pfvar::PF_RDR as u8;
+        assert_eq!(
+            NatRule::try_from(pf_rule).unwrap_err().kind(),
+            crate::ErrorKind::UnsupportedRule
+        );
+    }
+
+    #[test]
+    fn read_back_scrub_rule() {
+        let rule = ScrubRuleBuilder::default()
+            .action(ScrubRuleAction::NoScrub)
+            .direction(Direction::Out)
+            .build()
+            .unwrap();
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        assert_eq!(ScrubRule::try_from(pf_rule).unwrap(), rule);
+    }
+
+    #[test]
+    fn rule_kind() {
+        let scrub_rule = ScrubRuleBuilder::default()
+            .action(ScrubRuleAction::Scrub)
+            .build()
+            .unwrap();
+        assert_eq!(Rule::from(scrub_rule).kind(), RulesetKind::Scrub);
+
+        let nat_rule = NatRuleBuilder::default()
+            .action(NatRuleAction::NoNat)
+            .build()
+            .unwrap();
+        assert_eq!(Rule::from(nat_rule).kind(), RulesetKind::Nat);
+    }
+
+    #[test]
+    fn fingerprint_matches_read_back_rule() {
+        // Address family is left out so it has to be computed from the endpoints
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .proto(Proto::Tcp)
+            .to(Endpoint::new(Ipv4Addr::new(192, 168, 0, 1), 80))
+            .label("fingerprint")
+            .build()
+            .unwrap();
+
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        rule.try_copy_to(&mut pf_rule).unwrap();
+        let read_back = FilterRule::try_from(pf_rule).unwrap();
+        assert_ne!(read_back, rule);
+        assert_eq!(
+            read_back.fingerprint().unwrap(),
+            rule.fingerprint().unwrap()
+        );
+        assert_eq!(
+            Rule::from(rule.clone()).fingerprint().unwrap(),
+            rule.fingerprint().unwrap()
+        );
+    }
+
+    #[test]
+    fn fingerprint_differs_between_rules() {
+        let rule1 = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .label("a")
+            .build()
+            .unwrap();
+        let rule2 = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .label("b")
+            .build()
+            .unwrap();
+        assert_ne!(rule1.fingerprint().unwrap(), rule2.fingerprint().unwrap());
+    }
+
     #[test]
     fn state_policy_correct_default() {
         assert_eq!(
@@ -580,6 +1689,39 @@ mod filter_rule_tests {
         );
     }
 
+    #[test]
+    fn other_proto_is_normalized() {
+        let rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .keep_state(StatePolicy::Modulate)
+            .proto(Proto::Other(6))
+            .build()
+            .unwrap();
+        assert_eq!(rule.validate_state_policy().unwrap(), StatePolicy::Modulate);
+        let tcp_rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .keep_state(StatePolicy::Modulate)
+            .proto(Proto::Tcp)
+            .build()
+            .unwrap();
+        assert_eq!(rule, tcp_rule);
+
+        let listed = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .proto_any_of([Proto::Other(17), Proto::GRE])
+            .build()
+            .unwrap();
+        assert_eq!(listed.proto[..], [Proto::Udp, Proto::GRE]);
+
+        let redirect = RedirectRuleBuilder::default()
+            .action(RedirectRuleAction::Redirect)
+            .proto(Proto::Other(17))
+            .redirect_to(Ipv4Addr::new(127, 0, 0, 1))
+            .build()
+            .unwrap();
+        assert_eq!(redirect.proto, Proto::Udp);
+    }
+
     #[test]
     fn state_policy_incompatible_modulate() {
         assert!(
@@ -53,6 +53,30 @@ impl TryCopyTo<ffi::pfvar::pf_port_range> for Port {
     }
 }
 
+impl TryFrom<ffi::pfvar::pf_port_range> for Port {
+    type Error = crate::Error;
+
+    fn try_from(pf_port_range: ffi::pfvar::pf_port_range) -> crate::Result<Self> {
+        // convert port range from network byte order
+        let [start_port, end_port] = pf_port_range.port.map(u16::from_be);
+        Port::try_from_raw(pf_port_range.op, start_port, end_port)
+    }
+}
+
+impl Port {
+    /// Reads a `Port` back from a port operator and two ports in host byte order.
+    pub(crate) fn try_from_raw(op: u8, start_port: u16, end_port: u16) -> crate::Result<Self> {
+        if op == ffi::pfvar::PF_OP_NONE as u8 {
+            Ok(Port::Any)
+        } else if let Ok(modifier) = PortUnaryModifier::try_from(op) {
+            Ok(Port::One(start_port, modifier))
+        } else {
+            let modifier = PortRangeModifier::try_from(op)?;
+            Ok(Port::Range(start_port, end_port, modifier))
+        }
+    }
+}
+
 impl TryCopyTo<ffi::pfvar::pf_pool> for Port {
     type Error = crate::Error;
 
@@ -81,6 +105,15 @@ impl TryCopyTo<ffi::pfvar::pf_pool> for Port {
     }
 }
 
+impl TryFrom<ffi::pfvar::pf_pool> for Port {
+    type Error = crate::Error;
+
+    fn try_from(pf_pool: ffi::pfvar::pf_pool) -> crate::Result<Self> {
+        let [start_port, end_port] = pf_pool.proxy_port;
+        Port::try_from_raw(pf_pool.port_op, start_port, end_port)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PortUnaryModifier {
     Equal,
@@ -104,6 +137,21 @@ impl From<PortUnaryModifier> for u8 {
     }
 }
 
+impl TryFrom<u8> for PortUnaryModifier {
+    type Error = crate::Error;
+
+    fn try_from(op: u8) -> crate::Result<Self> {
+        use PortUnaryModifier::*;
+        [Equal, NotEqual, Greater, Less, GreaterOrEqual, LessOrEqual]
+            .into_iter()
+            .find(|&modifier| u8::from(modifier) == op)
+            .ok_or_else(|| {
+                let msg = format!("{op} is not a unary port operator");
+                Error::from(ErrorInternal::UnsupportedRule(msg))
+            })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PortRangeModifier {
     Exclusive,
@@ -120,3 +168,18 @@ impl From<PortRangeModifier> for u8 {
         }
     }
 }
+
+impl TryFrom<u8> for PortRangeModifier {
+    type Error = crate::Error;
+
+    fn try_from(op: u8) -> crate::Result<Self> {
+        use PortRangeModifier::*;
+        [Exclusive, Inclusive, Except]
+            .into_iter()
+            .find(|&modifier| u8::from(modifier) == op)
+            .ok_or_else(|| {
+                let msg = format!("{op} is not a port range operator");
+                Error::from(ErrorInternal::UnsupportedRule(msg))
+            })
+    }
+}
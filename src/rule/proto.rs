@@ -6,17 +6,39 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::{Error, ErrorInternal, Result};
-
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-#[repr(u8)]
 pub enum Proto {
     #[default]
-    Any = libc::IPPROTO_IP as u8,
-    Tcp = libc::IPPROTO_TCP as u8,
-    Udp = libc::IPPROTO_UDP as u8,
-    Icmp = libc::IPPROTO_ICMP as u8,
-    IcmpV6 = libc::IPPROTO_ICMPV6 as u8,
+    Any,
+    Tcp,
+    Udp,
+    Icmp,
+    IcmpV6,
+    /// Any other IP protocol, by number, such as `Proto::GRE`. Protocols with a variant of their
+    /// own are always read back from PF as that variant, and rule builders store them as that
+    /// variant too, so `Proto::Other(6)` is the same as `Proto::Tcp` in a rule.
+    Other(u8),
+}
+
+impl Proto {
+    /// Generic Routing Encapsulation, written as `proto gre` in pf.conf.
+    pub const GRE: Proto = Proto::Other(libc::IPPROTO_GRE as u8);
+    /// IPsec Encapsulating Security Payload, written as `proto esp` in pf.conf.
+    pub const ESP: Proto = Proto::Other(libc::IPPROTO_ESP as u8);
+    /// IPsec Authentication Header, written as `proto ah` in pf.conf.
+    pub const AH: Proto = Proto::Other(libc::IPPROTO_AH as u8);
+    /// Stream Control Transmission Protocol, written as `proto sctp` in pf.conf.
+    pub const SCTP: Proto = Proto::Other(libc::IPPROTO_SCTP as u8);
+    /// IPv4 encapsulated in IPv4.
+    pub const IPIP: Proto = Proto::Other(libc::IPPROTO_IPIP as u8);
+    /// IPv6 encapsulated in IPv4, as used by 6in4 and 6to4 tunnels.
+    pub const IPV6_IN_IPV4: Proto = Proto::Other(libc::IPPROTO_IPV6 as u8);
+
+    /// Returns this protocol as PF reads it back, with `Other` holding the number of a protocol
+    /// that has a variant of its own replaced by that variant.
+    pub(crate) fn normalized(self) -> Proto {
+        Proto::from(u8::from(self))
+    }
 }
 
 impl From<Proto> for u8 {
@@ -27,21 +49,45 @@ impl From<Proto> for u8 {
             Proto::Udp => libc::IPPROTO_UDP as u8,
             Proto::Icmp => libc::IPPROTO_ICMP as u8,
             Proto::IcmpV6 => libc::IPPROTO_ICMPV6 as u8,
+            Proto::Other(proto) => proto,
+        }
+    }
+}
+
+impl From<u8> for Proto {
+    fn from(proto: u8) -> Self {
+        match i32::from(proto) {
+            libc::IPPROTO_IP => Proto::Any,
+            libc::IPPROTO_TCP => Proto::Tcp,
+            libc::IPPROTO_UDP => Proto::Udp,
+            libc::IPPROTO_ICMP => Proto::Icmp,
+            libc::IPPROTO_ICMPV6 => Proto::IcmpV6,
+            _ => Proto::Other(proto),
         }
     }
 }
 
-impl TryFrom<u8> for Proto {
-    type Error = crate::Error;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn try_from(proto: u8) -> Result<Self> {
-        match proto {
-            v if v == Proto::Any as u8 => Ok(Proto::Any),
-            v if v == Proto::Tcp as u8 => Ok(Proto::Tcp),
-            v if v == Proto::Udp as u8 => Ok(Proto::Udp),
-            v if v == Proto::Icmp as u8 => Ok(Proto::Icmp),
-            v if v == Proto::IcmpV6 as u8 => Ok(Proto::IcmpV6),
-            _ => Err(Error::from(ErrorInternal::InvalidTransportProtocol(proto))),
+    #[test]
+    fn proto_numbers_round_trip() {
+        for number in 0..=u8::MAX {
+            assert_eq!(u8::from(Proto::from(number)), number);
         }
+        assert_eq!(Proto::from(6), Proto::Tcp);
+        assert_eq!(Proto::from(47), Proto::GRE);
+        assert_eq!(u8::from(Proto::ESP), 50);
+        assert_eq!(u8::from(Proto::IPV6_IN_IPV4), 41);
+    }
+
+    #[test]
+    fn other_proto_normalization() {
+        assert_eq!(Proto::Other(6).normalized(), Proto::Tcp);
+        assert_eq!(Proto::Other(0).normalized(), Proto::Any);
+        assert_eq!(Proto::Other(58).normalized(), Proto::IcmpV6);
+        assert_eq!(Proto::GRE.normalized(), Proto::GRE);
+        assert_eq!(Proto::Udp.normalized(), Proto::Udp);
     }
 }
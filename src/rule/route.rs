@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::{ffi, pooladdr::PoolAddr};
+use crate::{Error, ErrorInternal, Ip, Result, ffi, pooladdr::PoolAddr};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub enum Route {
@@ -38,6 +38,32 @@ impl Route {
             Route::DupTo(ref pool_addr) => Some(pool_addr),
         }
     }
+
+    /// Reads a `Route` back from the `rt` field of a `pf_rule`. The pool address is not part of
+    /// `pf_rule`, so it is left as `Ip::Any` and has to be filled in with `with_pool_addr`.
+    pub(crate) fn try_from_raw(rt: u8) -> Result<Self> {
+        let pool_addr = PoolAddr::from(Ip::Any);
+        match u32::from(rt) {
+            v if v == ffi::pfvar::PF_NOPFROUTE as u32 => Ok(Route::NoRoute),
+            v if v == ffi::pfvar::PF_ROUTETO as u32 => Ok(Route::RouteTo(pool_addr)),
+            v if v == ffi::pfvar::PF_REPLYTO as u32 => Ok(Route::ReplyTo(pool_addr)),
+            v if v == ffi::pfvar::PF_DUPTO as u32 => Ok(Route::DupTo(pool_addr)),
+            _ => {
+                let msg = format!("{rt} is not a supported route option");
+                Err(Error::from(ErrorInternal::UnsupportedRule(msg)))
+            }
+        }
+    }
+
+    /// Returns this route with its pool address replaced by `pool_addr`.
+    pub(crate) fn with_pool_addr(self, pool_addr: PoolAddr) -> Self {
+        match self {
+            Route::NoRoute => Route::NoRoute,
+            Route::RouteTo(_) => Route::RouteTo(pool_addr),
+            Route::ReplyTo(_) => Route::ReplyTo(pool_addr),
+            Route::DupTo(_) => Route::DupTo(pool_addr),
+        }
+    }
 }
 
 impl<'a> From<&'a Route> for u8 {
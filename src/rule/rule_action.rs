@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::{NatEndpoint, ffi};
+use crate::{Error, ErrorInternal, NatEndpoint, Result, ffi};
 
 /// Enum describing what should happen to a packet that matches a filter rule.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -35,6 +35,18 @@ impl FilterRuleAction {
             FilterRuleAction::Drop(action) => action.into(),
         }
     }
+
+    /// Reads a `FilterRuleAction` back from the `action` and `rule_flag` fields of a `pf_rule`.
+    pub(crate) fn try_from_raw(action: u8, rule_flag: u32) -> Result<Self> {
+        if action == ffi::pfvar::PF_PASS as u8 {
+            Ok(FilterRuleAction::Pass)
+        } else if action == ffi::pfvar::PF_DROP as u8 {
+            Ok(FilterRuleAction::Drop(DropAction::from(rule_flag)))
+        } else {
+            let msg = format!("{action} is not a filter rule action");
+            Err(Error::from(ErrorInternal::UnsupportedRule(msg)))
+        }
+    }
 }
 
 impl From<FilterRuleAction> for u8 {
@@ -58,6 +70,21 @@ impl From<DropAction> for u32 {
     }
 }
 
+impl From<u32> for DropAction {
+    fn from(rule_flag: u32) -> Self {
+        use crate::ffi::pfvar::*;
+        if rule_flag & PFRULE_RETURN != 0 {
+            DropAction::Return
+        } else if rule_flag & PFRULE_RETURNRST != 0 {
+            DropAction::ReturnRst
+        } else if rule_flag & PFRULE_RETURNICMP != 0 {
+            DropAction::ReturnIcmp
+        } else {
+            DropAction::Drop
+        }
+    }
+}
+
 /// Enum describing what should happen to a packet that matches a NAT rule.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum NatRuleAction {
@@ -90,6 +117,21 @@ impl From<RedirectRuleAction> for u8 {
     }
 }
 
+impl TryFrom<u8> for RedirectRuleAction {
+    type Error = crate::Error;
+
+    fn try_from(action: u8) -> Result<Self> {
+        use RedirectRuleAction::*;
+        [Redirect, NoRedirect]
+            .into_iter()
+            .find(|&rule_action| u8::from(rule_action) == action)
+            .ok_or_else(|| {
+                let msg = format!("{action} is not a redirect rule action");
+                Error::from(ErrorInternal::UnsupportedRule(msg))
+            })
+    }
+}
+
 /// Enum describing what should happen to a packet that matches a scrub rule.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ScrubRuleAction {
@@ -105,3 +147,18 @@ impl From<ScrubRuleAction> for u8 {
         }
     }
 }
+
+impl TryFrom<u8> for ScrubRuleAction {
+    type Error = crate::Error;
+
+    fn try_from(action: u8) -> Result<Self> {
+        use ScrubRuleAction::*;
+        [Scrub, NoScrub]
+            .into_iter()
+            .find(|&rule_action| u8::from(rule_action) == action)
+            .ok_or_else(|| {
+                let msg = format!("{action} is not a scrub rule action");
+                Error::from(ErrorInternal::UnsupportedRule(msg))
+            })
+    }
+}
@@ -45,6 +45,18 @@ impl From<RuleLog> for RuleLogSet {
     }
 }
 
+impl From<u8> for RuleLogSet {
+    fn from(log: u8) -> Self {
+        use RuleLog::*;
+        RuleLogSet(
+            [ExcludeMatchingState, IncludeMatchingState, SocketOwner]
+                .into_iter()
+                .filter(|&rule_log| log & u8::from(rule_log) != 0)
+                .collect(),
+        )
+    }
+}
+
 impl From<&RuleLogSet> for u8 {
     fn from(set: &RuleLogSet) -> Self {
         set.0.iter().fold(0, |acc, &x| acc | u8::from(x))
@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::ffi;
+use crate::{Error, ErrorInternal, Result, ffi};
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StatePolicy {
@@ -27,3 +27,18 @@ impl From<StatePolicy> for u8 {
         }
     }
 }
+
+impl TryFrom<u8> for StatePolicy {
+    type Error = crate::Error;
+
+    fn try_from(keep_state: u8) -> Result<Self> {
+        use StatePolicy::*;
+        [None, Keep, Modulate, SynProxy]
+            .into_iter()
+            .find(|&state_policy| u8::from(state_policy) == keep_state)
+            .ok_or_else(|| {
+                let msg = format!("{keep_state} is not a state policy");
+                Error::from(ErrorInternal::UnsupportedRule(msg))
+            })
+    }
+}
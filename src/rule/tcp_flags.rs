@@ -41,6 +41,18 @@ impl From<TcpFlag> for u8 {
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct TcpFlagSet(Vec<TcpFlag>);
 
+impl From<u8> for TcpFlagSet {
+    fn from(flags: u8) -> Self {
+        use TcpFlag::*;
+        TcpFlagSet(
+            [Fin, Syn, Rst, Psh, Ack, Urg, Ece, Cwr]
+                .into_iter()
+                .filter(|&tcp_flag| flags & u8::from(tcp_flag) != 0)
+                .collect(),
+        )
+    }
+}
+
 impl From<&TcpFlagSet> for u8 {
     fn from(set: &TcpFlagSet) -> Self {
         set.0.iter().fold(0, |acc, &x| acc | u8::from(x))
@@ -7,6 +7,7 @@
 // except according to those terms.
 
 use crate::{
+    Error, ErrorInternal, Result,
     conversion::CopyTo,
     ffi::pfvar::{self, pf_rule_uid},
 };
@@ -24,6 +25,20 @@ impl From<u32> for Id {
     }
 }
 
+impl Id {
+    /// Reads an `Id` back from the ids and operator of a `pf_rule_uid` or `pf_rule_gid`.
+    pub(crate) fn try_from_raw(ids: [u32; 2], op: u8) -> Result<Self> {
+        if op == pfvar::PF_OP_NONE as u8 {
+            Ok(Id::Any)
+        } else if let Ok(modifier) = IdUnaryModifier::try_from(op) {
+            Ok(Id::One(ids[0], modifier))
+        } else {
+            let modifier = IdRangeModifier::try_from(op)?;
+            Ok(Id::Range(ids[0], ids[1], modifier))
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Uid(pub Id);
 
@@ -39,6 +54,14 @@ impl<T: Into<Id>> From<T> for Uid {
     }
 }
 
+impl TryFrom<pf_rule_uid> for Uid {
+    type Error = crate::Error;
+
+    fn try_from(pf_rule_uid: pf_rule_uid) -> Result<Self> {
+        Id::try_from_raw(pf_rule_uid.uid, pf_rule_uid.op).map(Uid)
+    }
+}
+
 impl CopyTo<pf_rule_uid> for Uid {
     fn copy_to(&self, pf_rule_uid: &mut pf_rule_uid) {
         match self.0 {
@@ -84,6 +107,21 @@ impl From<IdUnaryModifier> for u8 {
     }
 }
 
+impl TryFrom<u8> for IdUnaryModifier {
+    type Error = crate::Error;
+
+    fn try_from(op: u8) -> Result<Self> {
+        use IdUnaryModifier::*;
+        [Equal, NotEqual, Less, LessOrEqual, Greater, GreaterOrEqual]
+            .into_iter()
+            .find(|&modifier| u8::from(modifier) == op)
+            .ok_or_else(|| {
+                let msg = format!("{op} is not a unary id operator");
+                Error::from(ErrorInternal::UnsupportedRule(msg))
+            })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IdRangeModifier {
     Exclusive,
@@ -100,3 +138,18 @@ impl From<IdRangeModifier> for u8 {
         }
     }
 }
+
+impl TryFrom<u8> for IdRangeModifier {
+    type Error = crate::Error;
+
+    fn try_from(op: u8) -> Result<Self> {
+        use IdRangeModifier::*;
+        [Exclusive, Inclusive, Except]
+            .into_iter()
+            .find(|&modifier| u8::from(modifier) == op)
+            .ok_or_else(|| {
+                let msg = format!("{op} is not an id range operator");
+                Error::from(ErrorInternal::UnsupportedRule(msg))
+            })
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2025 Mullvad VPN AB.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{conversion::CopyTo, ffi};
+
+/// Enum describing where in an anchor a rule is inserted. Rules are numbered by their position in
+/// the anchor, in the order PF evaluates them, starting at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RulePosition {
+    /// Insert the rule before all other rules.
+    Head,
+    /// Insert the rule after all other rules.
+    Tail,
+    /// Insert the rule right before the rule with the given number.
+    Before(u32),
+    /// Insert the rule right after the rule with the given number.
+    After(u32),
+}
+
+impl RulePosition {
+    /// Returns where to insert the rule `offset` places after the first one, when inserting
+    /// several rules at this position and keeping them in order. Returns `None` if the rule
+    /// number does not fit in a `u32`.
+    pub(crate) fn offset_by(self, offset: u32) -> Option<RulePosition> {
+        match self {
+            RulePosition::Head if offset > 0 => Some(RulePosition::After(offset - 1)),
+            RulePosition::Head | RulePosition::Tail => Some(self),
+            RulePosition::Before(nr) => nr.checked_add(offset).map(RulePosition::Before),
+            RulePosition::After(nr) => nr.checked_add(offset).map(RulePosition::After),
+        }
+    }
+}
+
+/// Enum describing how many of the matching rules to remove when removing rules by value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemoveMode {
+    /// Remove only the first matching rule, in the order PF evaluates them.
+    First,
+    /// Remove all matching rules.
+    All,
+}
+
+impl CopyTo<ffi::pfvar::pfioc_rule> for RulePosition {
+    fn copy_to(&self, pfioc_rule: &mut ffi::pfvar::pfioc_rule) {
+        let (action, nr) = match *self {
+            RulePosition::Head => (ffi::pfvar::PF_CHANGE_ADD_HEAD, 0),
+            RulePosition::Tail => (ffi::pfvar::PF_CHANGE_ADD_TAIL, 0),
+            RulePosition::Before(nr) => (ffi::pfvar::PF_CHANGE_ADD_BEFORE, nr),
+            RulePosition::After(nr) => (ffi::pfvar::PF_CHANGE_ADD_AFTER, nr),
+        };
+        pfioc_rule.action = action as u32;
+        pfioc_rule.nr = nr;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zerocopy::FromZeros;
+
+    #[test]
+    fn copy_position_to_pfioc_rule() {
+        let mut pfioc_rule = ffi::pfvar::pfioc_rule::new_zeroed();
+        RulePosition::After(7).copy_to(&mut pfioc_rule);
+        assert_eq!(pfioc_rule.action, ffi::pfvar::PF_CHANGE_ADD_AFTER as u32);
+        assert_eq!(pfioc_rule.nr, 7);
+
+        RulePosition::Head.copy_to(&mut pfioc_rule);
+        assert_eq!(pfioc_rule.action, ffi::pfvar::PF_CHANGE_ADD_HEAD as u32);
+        assert_eq!(pfioc_rule.nr, 0);
+    }
+
+    #[test]
+    fn offset_positions_keep_order() {
+        let positions =
+            |position: RulePosition| (0..3).map(move |i| position.offset_by(i).unwrap());
+        assert!(positions(RulePosition::Tail).all(|p| p == RulePosition::Tail));
+        assert!(positions(RulePosition::Head).eq([
+            RulePosition::Head,
+            RulePosition::After(0),
+            RulePosition::After(1)
+        ]));
+        assert!(positions(RulePosition::Before(4)).eq([
+            RulePosition::Before(4),
+            RulePosition::Before(5),
+            RulePosition::Before(6)
+        ]));
+        assert!(positions(RulePosition::After(4)).eq([
+            RulePosition::After(4),
+            RulePosition::After(5),
+            RulePosition::After(6)
+        ]));
+    }
+
+    #[test]
+    fn offset_position_overflow() {
+        assert_eq!(
+            RulePosition::Before(u32::MAX).offset_by(0),
+            Some(RulePosition::Before(u32::MAX))
+        );
+        assert_eq!(RulePosition::Before(u32::MAX).offset_by(1), None);
+        assert_eq!(RulePosition::After(u32::MAX - 1).offset_by(2), None);
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2025 Mullvad VPN AB.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{ErrorInternal, Result, ffi};
+
+/// Counters PF keeps for a single rule in an anchor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RuleStats {
+    nr: u32,
+    label: String,
+    evaluations: u64,
+    packets: [u64; 2],
+    bytes: [u64; 2],
+    states: u32,
+    src_nodes: u32,
+}
+
+impl RuleStats {
+    /// Position of the rule in its anchor, in the order PF evaluates the rules.
+    pub fn nr(&self) -> u32 {
+        self.nr
+    }
+
+    /// Accessor for `label`
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Number of times the rule has been evaluated against a packet.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Number of inbound packets that matched the rule.
+    pub fn packets_in(&self) -> u64 {
+        self.packets[0]
+    }
+
+    /// Number of outbound packets that matched the rule.
+    pub fn packets_out(&self) -> u64 {
+        self.packets[1]
+    }
+
+    /// Number of inbound bytes that matched the rule.
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes[0]
+    }
+
+    /// Number of outbound bytes that matched the rule.
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes[1]
+    }
+
+    /// Number of states currently created by the rule.
+    pub fn states(&self) -> u32 {
+        self.states
+    }
+
+    /// Number of source tracking nodes currently created by the rule.
+    pub fn src_nodes(&self) -> u32 {
+        self.src_nodes
+    }
+}
+
+impl TryFrom<ffi::pfvar::pf_rule> for RuleStats {
+    type Error = crate::Error;
+
+    fn try_from(pf_rule: ffi::pfvar::pf_rule) -> Result<Self> {
+        Ok(RuleStats {
+            nr: pf_rule.nr,
+            label: crate::cstr_to_string(&pf_rule.label).map_err(ErrorInternal::InvalidLabel)?,
+            evaluations: pf_rule.evaluations,
+            packets: pf_rule.packets,
+            bytes: pf_rule.bytes,
+            states: pf_rule.states,
+            src_nodes: pf_rule.src_nodes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::TryCopyTo;
+    use zerocopy::FromZeros;
+
+    #[test]
+    fn stats_from_pf_rule() {
+        let mut pf_rule = ffi::pfvar::pf_rule::new_zeroed();
+        pf_rule.nr = 3;
+        "block".try_copy_to(&mut pf_rule.label).unwrap();
+        pf_rule.evaluations = 100;
+        pf_rule.packets = [10, 20];
+        pf_rule.bytes = [1000, 2000];
+        pf_rule.states = 4;
+        pf_rule.src_nodes = 1;
+
+        let stats = RuleStats::try_from(pf_rule).unwrap();
+        assert_eq!(stats.nr(), 3);
+        assert_eq!(stats.label(), "block");
+        assert_eq!(stats.evaluations(), 100);
+        assert_eq!(stats.packets_in(), 10);
+        assert_eq!(stats.packets_out(), 20);
+        assert_eq!(stats.bytes_in(), 1000);
+        assert_eq!(stats.bytes_out(), 2000);
+        assert_eq!(stats.states(), 4);
+        assert_eq!(stats.src_nodes(), 1);
+    }
+}
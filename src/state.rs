@@ -39,8 +39,10 @@ impl State {
     }
 
     /// Return the transport protocol for this state
+    // All protocol numbers are valid since the addition of `Proto::Other`, so this never fails.
+    // The `Result` is kept to not break the API until the next breaking release.
     pub fn proto(&self) -> Result<Proto> {
-        Proto::try_from(self.sync_state.proto)
+        Ok(Proto::from(self.sync_state.proto))
     }
 
     /// Return the local socket address for this state
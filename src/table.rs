@@ -0,0 +1,450 @@
+// Copyright 2025 Mullvad VPN AB.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::{Error, ErrorInternal, Result, conversion::CopyTo, ffi, utils::FixedName};
+use ipnetwork::IpNetwork;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::{Duration, SystemTime},
+};
+
+bitflags::bitflags! {
+    /// Flags of a PF table. Only `PERSIST` and `CONST` can be set by the user, the other flags
+    /// are maintained by PF and only reported back when listing tables.
+    ///
+    /// PF on macOS always keeps per-address counters for tables, so there is no equivalent of
+    /// the `counters` table option found on other BSDs.
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct TableFlags: u32 {
+        /// Keep the table even when no rule refers to it. Equivalent to PFR_TFLAG_PERSIST.
+        const PERSIST = ffi::pfvar::PFR_TFLAG_PERSIST;
+        /// The addresses of the table can not be changed. Equivalent to PFR_TFLAG_CONST.
+        const CONST = ffi::pfvar::PFR_TFLAG_CONST;
+        /// The table is part of the active ruleset. Equivalent to PFR_TFLAG_ACTIVE.
+        const ACTIVE = ffi::pfvar::PFR_TFLAG_ACTIVE;
+        /// The table is part of an open transaction. Equivalent to PFR_TFLAG_INACTIVE.
+        const INACTIVE = ffi::pfvar::PFR_TFLAG_INACTIVE;
+        /// The table is referenced by a rule. Equivalent to PFR_TFLAG_REFERENCED.
+        const REFERENCED = ffi::pfvar::PFR_TFLAG_REFERENCED;
+        /// The table is referenced by a rule in an anchor below the table's own anchor.
+        /// Equivalent to PFR_TFLAG_REFDANCHOR.
+        const REFDANCHOR = ffi::pfvar::PFR_TFLAG_REFDANCHOR;
+    }
+}
+
+impl TableFlags {
+    /// Returns an `InvalidTableFlags` error if any flag other than `PERSIST` and `CONST` is set.
+    /// PF rejects the whole request if a table is created with any of the flags it maintains.
+    pub(crate) fn validate_settable(self) -> Result<()> {
+        if self.bits() & !ffi::pfvar::PFR_TFLAG_USRMASK != 0 {
+            return Err(Error::from(ErrorInternal::InvalidTableFlags(self)));
+        }
+        Ok(())
+    }
+}
+
+/// The name of a PF table, as used to refer to a table from a rule.
+///
+/// The name is validated on creation to fit into PF's fixed size table name buffer, which lets
+/// `TableName`, and the rule types containing it, stay `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableName(FixedName<{ ffi::pfvar::PF_TABLE_NAME_SIZE as usize }>);
+
+impl TableName {
+    /// Returns a `TableName` if `name` is a valid PF table name, meaning it is not empty, does not
+    /// contain any null byte and is shorter than `PF_TABLE_NAME_SIZE` bytes.
+    pub fn new(name: &str) -> Result<Self> {
+        FixedName::new(name, ErrorInternal::InvalidTableName).map(TableName)
+    }
+
+    /// Reads a `TableName` back from a raw, null terminated, table name.
+    pub(crate) fn try_from_raw(name: &[std::os::raw::c_char]) -> Result<Self> {
+        FixedName::try_from_raw(name, ErrorInternal::InvalidTableName).map(TableName)
+    }
+}
+
+impl_fixed_name!(TableName);
+
+/// A table in PF together with its flags and the number of addresses it holds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TableInfo {
+    anchor: String,
+    name: String,
+    flags: TableFlags,
+    addr_count: u32,
+}
+
+impl TableInfo {
+    /// Full path of the anchor the table belongs to. Empty for tables in the main ruleset.
+    pub fn anchor(&self) -> &str {
+        &self.anchor
+    }
+
+    /// Accessor for `name`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Accessor for `flags`
+    pub fn flags(&self) -> TableFlags {
+        self.flags
+    }
+
+    /// Number of addresses currently in the table.
+    pub fn addr_count(&self) -> u32 {
+        self.addr_count
+    }
+}
+
+impl TryFrom<ffi::pfvar::pfr_tstats> for TableInfo {
+    type Error = crate::Error;
+
+    fn try_from(tstats: ffi::pfvar::pfr_tstats) -> Result<Self> {
+        let table = &tstats.pfrts_t;
+        Ok(TableInfo {
+            anchor: crate::cstr_to_string(&table.pfrt_anchor)
+                .map_err(ErrorInternal::InvalidAnchorName)?,
+            name: crate::cstr_to_string(&table.pfrt_name)
+                .map_err(ErrorInternal::InvalidTableName)?,
+            flags: TableFlags::from_bits_retain(table.pfrt_flags),
+            addr_count: u32::try_from(tstats.pfrts_cnt).unwrap_or_default(),
+        })
+    }
+}
+
+/// An address in a PF table. Addresses are networks that the table matches, or excludes if the
+/// entry is negated, as in `! 10.0.0.0/8`. For an address matched by several entries of a table,
+/// the most specific one decides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableAddr {
+    network: IpNetwork,
+    negated: bool,
+}
+
+impl TableAddr {
+    /// Returns an entry matching `network`.
+    pub fn new(network: IpNetwork) -> Self {
+        TableAddr {
+            network,
+            negated: false,
+        }
+    }
+
+    /// Returns an entry excluding `network` from the table.
+    pub fn negated(network: IpNetwork) -> Self {
+        TableAddr {
+            network,
+            negated: true,
+        }
+    }
+
+    /// Accessor for `network`
+    pub fn network(&self) -> IpNetwork {
+        self.network
+    }
+
+    /// Returns true if the entry excludes its network from the table.
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+}
+
+impl From<IpNetwork> for TableAddr {
+    fn from(network: IpNetwork) -> Self {
+        TableAddr::new(network)
+    }
+}
+
+impl From<IpAddr> for TableAddr {
+    fn from(ip: IpAddr) -> Self {
+        TableAddr::new(IpNetwork::from(ip))
+    }
+}
+
+impl From<Ipv4Addr> for TableAddr {
+    fn from(ip: Ipv4Addr) -> Self {
+        TableAddr::from(IpAddr::V4(ip))
+    }
+}
+
+impl From<Ipv6Addr> for TableAddr {
+    fn from(ip: Ipv6Addr) -> Self {
+        TableAddr::from(IpAddr::V6(ip))
+    }
+}
+
+impl CopyTo<ffi::pfvar::pfr_addr> for TableAddr {
+    fn copy_to(&self, pfr_addr: &mut ffi::pfvar::pfr_addr) {
+        // PF rejects entries with bits set outside of the prefix, so only the network address
+        // is copied.
+        match self.network.network() {
+            IpAddr::V4(ip) => {
+                pfr_addr.pfra_af = ffi::pfvar::PF_INET as u8;
+                ip.copy_to(unsafe { &mut pfr_addr.pfra_u._pfra_ip4addr });
+            }
+            IpAddr::V6(ip) => {
+                pfr_addr.pfra_af = ffi::pfvar::PF_INET6 as u8;
+                ip.copy_to(unsafe { &mut pfr_addr.pfra_u._pfra_ip6addr });
+            }
+        }
+        pfr_addr.pfra_net = self.network.prefix();
+        pfr_addr.pfra_not = self.negated as u8;
+    }
+}
+
+impl TryFrom<ffi::pfvar::pfr_addr> for TableAddr {
+    type Error = crate::Error;
+
+    fn try_from(pfr_addr: ffi::pfvar::pfr_addr) -> Result<Self> {
+        // SAFETY: All fields of the address union are plain byte arrays, so any bit pattern is
+        // valid.
+        let ip = match u32::from(pfr_addr.pfra_af) {
+            ffi::pfvar::PF_INET => {
+                let in_addr = unsafe { pfr_addr.pfra_u._pfra_ip4addr };
+                IpAddr::from(Ipv4Addr::from(u32::from_be(in_addr.s_addr)))
+            }
+            ffi::pfvar::PF_INET6 => {
+                let in6_addr = unsafe { pfr_addr.pfra_u._pfra_ip6addr };
+                IpAddr::from(unsafe { in6_addr.__u6_addr.__u6_addr8 })
+            }
+            _ => {
+                return Err(Error::from(ErrorInternal::InvalidTableAddress(
+                    "Invalid address family",
+                )));
+            }
+        };
+        let network = IpNetwork::new(ip, pfr_addr.pfra_net)
+            .map_err(|_| ErrorInternal::InvalidTableAddress("Invalid prefix length"))?;
+        Ok(TableAddr {
+            network,
+            negated: pfr_addr.pfra_not != 0,
+        })
+    }
+}
+
+/// Packet and byte counters of a table address, for the packets with one verdict.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableAddrCounters {
+    packets: [u64; 2],
+    bytes: [u64; 2],
+}
+
+impl TableAddrCounters {
+    /// Number of inbound packets that matched the address.
+    pub fn packets_in(&self) -> u64 {
+        self.packets[ffi::pfvar::PFR_DIR_IN as usize]
+    }
+
+    /// Number of outbound packets that matched the address.
+    pub fn packets_out(&self) -> u64 {
+        self.packets[ffi::pfvar::PFR_DIR_OUT as usize]
+    }
+
+    /// Number of inbound bytes that matched the address.
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes[ffi::pfvar::PFR_DIR_IN as usize]
+    }
+
+    /// Number of outbound bytes that matched the address.
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes[ffi::pfvar::PFR_DIR_OUT as usize]
+    }
+}
+
+/// Counters PF keeps for a single address in a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableAddrStats {
+    addr: TableAddr,
+    passed: TableAddrCounters,
+    blocked: TableAddrCounters,
+    zeroed_at: SystemTime,
+}
+
+impl TableAddrStats {
+    /// Accessor for `addr`
+    pub fn addr(&self) -> TableAddr {
+        self.addr
+    }
+
+    /// Counters for packets that were passed by the rules referring to the table.
+    pub fn passed(&self) -> &TableAddrCounters {
+        &self.passed
+    }
+
+    /// Counters for packets that were blocked by the rules referring to the table.
+    pub fn blocked(&self) -> &TableAddrCounters {
+        &self.blocked
+    }
+
+    /// Time the address was added to the table, or the time its counters were last cleared.
+    pub fn zeroed_at(&self) -> SystemTime {
+        self.zeroed_at
+    }
+}
+
+impl TryFrom<ffi::pfvar::pfr_astats> for TableAddrStats {
+    type Error = crate::Error;
+
+    fn try_from(astats: ffi::pfvar::pfr_astats) -> Result<Self> {
+        // The counters are indexed by direction first and verdict second
+        let counters = |op: ffi::pfvar::_bindgen_ty_20| TableAddrCounters {
+            packets: astats.pfras_packets.map(|dir| dir[op as usize]),
+            bytes: astats.pfras_bytes.map(|dir| dir[op as usize]),
+        };
+        Ok(TableAddrStats {
+            addr: TableAddr::try_from(astats.pfras_a)?,
+            passed: counters(ffi::pfvar::PFR_OP_PASS),
+            blocked: counters(ffi::pfvar::PFR_OP_BLOCK),
+            zeroed_at: SystemTime::UNIX_EPOCH + Duration::from_secs(astats.pfras_tzero),
+        })
+    }
+}
+
+/// Number of table entries affected by replacing the addresses of a table, as reported by PF.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableChanges {
+    pub(crate) added: u32,
+    pub(crate) deleted: u32,
+    pub(crate) changed: u32,
+}
+
+impl TableChanges {
+    /// Number of addresses that were not in the table before.
+    pub fn added(&self) -> u32 {
+        self.added
+    }
+
+    /// Number of addresses that were removed from the table.
+    pub fn deleted(&self) -> u32 {
+        self.deleted
+    }
+
+    /// Number of addresses that stayed in the table, but were negated or un-negated.
+    pub fn changed(&self) -> u32 {
+        self.changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::TryCopyTo;
+    use zerocopy::FromZeros;
+
+    #[test]
+    fn table_info_from_tstats() {
+        let mut tstats = ffi::pfvar::pfr_tstats::new_zeroed();
+        "outer/inner"
+            .try_copy_to(&mut tstats.pfrts_t.pfrt_anchor)
+            .unwrap();
+        "blocklist"
+            .try_copy_to(&mut tstats.pfrts_t.pfrt_name)
+            .unwrap();
+        tstats.pfrts_t.pfrt_flags = ffi::pfvar::PFR_TFLAG_PERSIST | ffi::pfvar::PFR_TFLAG_ACTIVE;
+        tstats.pfrts_cnt = 12;
+
+        let info = TableInfo::try_from(tstats).unwrap();
+        assert_eq!(info.anchor(), "outer/inner");
+        assert_eq!(info.name(), "blocklist");
+        assert_eq!(info.flags(), TableFlags::PERSIST | TableFlags::ACTIVE);
+        assert_eq!(info.addr_count(), 12);
+    }
+
+    #[test]
+    fn table_name_validation() {
+        assert_eq!(TableName::new("relays").unwrap().as_str(), "relays");
+        assert_eq!("relays".parse::<TableName>().unwrap().to_string(), "relays");
+        assert_eq!(TableName::new(&"a".repeat(31)).unwrap().as_str().len(), 31);
+
+        for name in ["", "a\0b", &"a".repeat(32)] {
+            assert_eq!(
+                TableName::new(name).unwrap_err().kind(),
+                crate::ErrorKind::InvalidTableName
+            );
+        }
+    }
+
+    #[test]
+    fn table_flags_validation() {
+        for flags in [
+            TableFlags::empty(),
+            TableFlags::PERSIST,
+            TableFlags::PERSIST | TableFlags::CONST,
+        ] {
+            assert!(flags.validate_settable().is_ok());
+        }
+
+        for flags in [
+            TableFlags::ACTIVE,
+            TableFlags::CONST | TableFlags::REFERENCED,
+            TableFlags::all(),
+        ] {
+            assert_eq!(
+                flags.validate_settable().unwrap_err().kind(),
+                crate::ErrorKind::InvalidTableFlags
+            );
+        }
+    }
+
+    #[test]
+    fn table_addr_round_trip() {
+        let addrs = [
+            TableAddr::new("10.0.0.0/8".parse().unwrap()),
+            TableAddr::negated("10.1.2.3/32".parse().unwrap()),
+            TableAddr::from(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1)),
+            TableAddr::negated("fd00::/16".parse().unwrap()),
+        ];
+        for addr in addrs {
+            let mut pfr_addr = ffi::pfvar::pfr_addr::new_zeroed();
+            addr.copy_to(&mut pfr_addr);
+            assert_eq!(TableAddr::try_from(pfr_addr).unwrap(), addr);
+        }
+    }
+
+    #[test]
+    fn table_addr_stats_from_astats() {
+        let mut astats = ffi::pfvar::pfr_astats::new_zeroed();
+        TableAddr::negated("10.0.0.0/8".parse().unwrap()).copy_to(&mut astats.pfras_a);
+        // [direction][verdict], with direction in, out and verdict block, pass
+        astats.pfras_packets = [[1, 2], [3, 4]];
+        astats.pfras_bytes = [[10, 20], [30, 40]];
+        astats.pfras_tzero = 1_700_000_000;
+
+        let stats = TableAddrStats::try_from(astats).unwrap();
+        assert_eq!(
+            stats.addr(),
+            TableAddr::negated("10.0.0.0/8".parse().unwrap())
+        );
+        assert_eq!(stats.blocked().packets_in(), 1);
+        assert_eq!(stats.passed().packets_in(), 2);
+        assert_eq!(stats.blocked().packets_out(), 3);
+        assert_eq!(stats.passed().packets_out(), 4);
+        assert_eq!(stats.blocked().bytes_in(), 10);
+        assert_eq!(stats.passed().bytes_in(), 20);
+        assert_eq!(stats.blocked().bytes_out(), 30);
+        assert_eq!(stats.passed().bytes_out(), 40);
+        assert_eq!(
+            stats.zeroed_at(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn table_addr_copies_network_address() {
+        let mut pfr_addr = ffi::pfvar::pfr_addr::new_zeroed();
+        TableAddr::new("192.168.1.10/24".parse().unwrap()).copy_to(&mut pfr_addr);
+        assert_eq!(
+            unsafe { pfr_addr.pfra_u._pfra_ip4addr.s_addr },
+            u32::from(Ipv4Addr::new(192, 168, 1, 0)).to_be()
+        );
+        assert_eq!(pfr_addr.pfra_net, 24);
+        assert_eq!(pfr_addr.pfra_af, ffi::pfvar::PF_INET as u8);
+        assert_eq!(pfr_addr.pfra_not, 0);
+    }
+}
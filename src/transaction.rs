@@ -9,8 +9,8 @@
 use zerocopy::FromZeros;
 
 use crate::{
-    FilterRule, NatRule, PoolAddrList, RedirectRule, Result, RulesetKind, ScrubRule,
-    conversion::TryCopyTo, ffi, utils,
+    FilterRule, NatRule, PoolAddrList, RedirectRule, Result, Rule, RulesetKind, ScrubRule,
+    TableAddr, TableFlags, TableName, conversion::TryCopyTo, ffi, utils,
 };
 use core::slice;
 use std::{
@@ -19,6 +19,10 @@ use std::{
     os::unix::io::{AsRawFd, RawFd},
 };
 
+/// Ruleset number of the tables of an anchor in a transaction. Defined as `PF_RULESET_MAX + 1` in
+/// pfvar.h, which bindgen is unable to evaluate.
+const PF_RULESET_TABLE: i32 = ffi::pfvar::PF_RULESET_MAX as i32 + 1;
+
 /// Structure that allows to manipulate rules in batches
 #[derive(Debug)]
 pub struct Transaction {
@@ -90,10 +94,16 @@ impl Transaction {
                     .map(|rules| (anchor.clone(), rules))
             })
             .collect();
+        let table_changes: Vec<(String, Vec<TableDefinition>)> = self
+            .change_by_anchor
+            .iter_mut()
+            .filter(|(_, change)| !change.tables.is_empty())
+            .map(|(anchor, change)| (anchor.clone(), mem::take(&mut change.tables)))
+            .collect();
 
         // create one transaction element for each unique combination of anchor name and
         // `RulesetKind` and order them so elements for filter rules go first followed by redirect
-        // rules
+        // rules. Tables go last, after all the rules that may refer to them.
         let mut pfioc_elements: Vec<ffi::pfvar::pfioc_trans_pfioc_trans_e> = filter_changes
             .iter()
             .map(|(anchor, _)| Self::new_trans_element(anchor, RulesetKind::Filter))
@@ -112,6 +122,11 @@ impl Transaction {
                     .iter()
                     .map(|(anchor, _)| Self::new_trans_element(anchor, RulesetKind::Scrub)),
             )
+            .chain(
+                table_changes
+                    .iter()
+                    .map(|(anchor, _)| Self::new_table_trans_element(anchor)),
+            )
             .collect::<Result<_>>()?;
         Self::setup_trans(&mut pfioc_trans, pfioc_elements.as_mut_slice());
 
@@ -126,7 +141,9 @@ impl Transaction {
             filter_changes.into_iter().zip(ticket_iterator.by_ref())
         {
             for filter_rule in filter_rules.iter() {
-                Self::add_filter_rule(fd, &anchor_name, filter_rule, ticket)?;
+                for expanded_rule in filter_rule.expand()? {
+                    Self::add_filter_rule(fd, &anchor_name, &expanded_rule, ticket)?;
+                }
             }
         }
 
@@ -157,6 +174,15 @@ impl Transaction {
             }
         }
 
+        // define tables in transaction
+        for ((anchor_name, tables), ticket) in
+            table_changes.into_iter().zip(ticket_iterator.by_ref())
+        {
+            for table in tables.iter() {
+                Self::define_table(fd, &anchor_name, table, ticket)?;
+            }
+        }
+
         ioctl_guard!(ffi::pf_commit_trans(fd, &mut pfioc_trans))
     }
 
@@ -264,6 +290,18 @@ impl Transaction {
         ioctl_guard!(ffi::pf_add_rule(fd, &mut pfioc_rule))
     }
 
+    /// Internal helper to define a table together with its addresses in transaction
+    fn define_table(fd: RawFd, anchor: &str, table: &TableDefinition, ticket: u32) -> Result<()> {
+        table.flags.validate_settable()?;
+        let mut addrs = crate::to_pfr_addrs(table.addrs.iter().copied());
+        let mut pfioc_table =
+            crate::setup_pfioc_table_addrs(anchor, table.name.as_str(), &mut addrs)?;
+        pfioc_table.pfrio_table.pfrt_flags = table.flags.bits();
+        pfioc_table.pfrio_flags = ffi::pfvar::PFR_FLAG_ADDRSTOO as i32;
+        pfioc_table.pfrio_ticket = ticket;
+        ioctl_guard!(ffi::pf_define_table(fd, &mut pfioc_table))
+    }
+
     /// Internal helper to wire up pfioc_trans and pfioc_trans_e
     fn setup_trans(
         pfioc_trans: &mut ffi::pfvar::pfioc_trans,
@@ -284,18 +322,37 @@ impl Transaction {
         utils::copy_anchor_name(anchor, &mut pfioc_trans_e.anchor[..])?;
         Ok(pfioc_trans_e)
     }
+
+    /// Internal helper to initialize pfioc_trans_e for the tables of an anchor
+    fn new_table_trans_element(anchor: &str) -> Result<ffi::pfvar::pfioc_trans_pfioc_trans_e> {
+        let mut pfioc_trans_e = ffi::pfvar::pfioc_trans_pfioc_trans_e::new_zeroed();
+        pfioc_trans_e.rs_num = PF_RULESET_TABLE;
+        utils::copy_anchor_name(anchor, &mut pfioc_trans_e.anchor[..])?;
+        Ok(pfioc_trans_e)
+    }
 }
 
 /// Structure that describes anchor rules manipulation allowing for targeted changes in anchors.
 /// The rules set to this structure will replace the active rules by transaction.
 /// Not setting either of rules will leave active rules untouched by transaction.
 /// In contrast, setting an empty vector for either of rules will remove the corresponding rules.
+/// Tables set to this structure replace the tables with the same name in the anchor, in the same
+/// transaction as the rules. Other tables in the anchor are left untouched.
 #[derive(Debug)]
 pub struct AnchorChange {
     filter_rules: Option<Vec<FilterRule>>,
     nat_rules: Option<Vec<NatRule>>,
     redirect_rules: Option<Vec<RedirectRule>>,
     scrub_rules: Option<Vec<ScrubRule>>,
+    tables: Vec<TableDefinition>,
+}
+
+/// A table together with its contents, as defined in an `AnchorChange`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TableDefinition {
+    name: TableName,
+    addrs: Vec<TableAddr>,
+    flags: TableFlags,
 }
 
 impl Default for AnchorChange {
@@ -312,6 +369,7 @@ impl AnchorChange {
             nat_rules: None,
             redirect_rules: None,
             scrub_rules: None,
+            tables: Vec::new(),
         }
     }
 
@@ -330,4 +388,109 @@ impl AnchorChange {
     pub fn set_scrub_rules(&mut self, rules: Vec<ScrubRule>) {
         self.scrub_rules = Some(rules);
     }
+
+    /// Sets the table `name` to contain exactly `addrs` and have the given flags once the
+    /// transaction is committed. Setting the same table again replaces the previous definition.
+    ///
+    /// Like with `PfCtl::create_table`, a table that no rule refers to is only kept if it has
+    /// `TableFlags::PERSIST`. Flags other than `PERSIST` and `CONST` make the transaction fail
+    /// with an `InvalidTableFlags` error when committed.
+    pub fn set_table<A: Into<TableAddr>>(
+        &mut self,
+        name: TableName,
+        addrs: impl IntoIterator<Item = A>,
+        flags: TableFlags,
+    ) {
+        let table = TableDefinition {
+            name,
+            addrs: addrs.into_iter().map(Into::into).collect(),
+            flags,
+        };
+        match self
+            .tables
+            .iter_mut()
+            .find(|existing| existing.name == name)
+        {
+            Some(existing) => *existing = table,
+            None => self.tables.push(table),
+        }
+    }
+
+    /// Appends a rule to the rules of its kind. The first rule pushed of a kind starts a new list
+    /// of rules of that kind, replacing the active rules of that kind by transaction.
+    pub fn push(&mut self, rule: impl Into<Rule>) {
+        match rule.into() {
+            Rule::Filter(rule) => self.filter_rules.get_or_insert_with(Vec::new).push(rule),
+            Rule::Nat(rule) => self.nat_rules.get_or_insert_with(Vec::new).push(rule),
+            Rule::Redirect(rule) => self.redirect_rules.get_or_insert_with(Vec::new).push(rule),
+            Rule::Scrub(rule) => self.scrub_rules.get_or_insert_with(Vec::new).push(rule),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        FilterRuleAction, FilterRuleBuilder, NatRuleAction, NatRuleBuilder, ScrubRuleAction,
+        ScrubRuleBuilder,
+    };
+
+    #[test]
+    fn push_sorts_rules_by_kind() {
+        let filter_rule = FilterRuleBuilder::default()
+            .action(FilterRuleAction::Pass)
+            .build()
+            .unwrap();
+        let nat_rule = NatRuleBuilder::default()
+            .action(NatRuleAction::NoNat)
+            .build()
+            .unwrap();
+        let scrub_rule = ScrubRuleBuilder::default()
+            .action(ScrubRuleAction::Scrub)
+            .build()
+            .unwrap();
+
+        let mut change = AnchorChange::new();
+        change.push(filter_rule.clone());
+        change.push(Rule::Nat(nat_rule.clone()));
+        change.push(filter_rule.clone());
+        change.push(scrub_rule.clone());
+
+        assert_eq!(
+            change.filter_rules,
+            Some(vec![filter_rule.clone(), filter_rule])
+        );
+        assert_eq!(change.nat_rules, Some(vec![nat_rule]));
+        assert_eq!(change.redirect_rules, None);
+        assert_eq!(change.scrub_rules, Some(vec![scrub_rule]));
+    }
+
+    #[test]
+    fn set_table_replaces_previous_definition() {
+        let relays = TableName::new("relays").unwrap();
+        let allowed = TableName::new("allowed").unwrap();
+        let addr = TableAddr::new("10.0.0.0/8".parse().unwrap());
+
+        let mut change = AnchorChange::new();
+        change.set_table(relays, [addr], TableFlags::PERSIST);
+        change.set_table(allowed, Vec::<TableAddr>::new(), TableFlags::CONST);
+        change.set_table(relays, Vec::<TableAddr>::new(), TableFlags::empty());
+
+        assert_eq!(
+            change.tables,
+            vec![
+                TableDefinition {
+                    name: relays,
+                    addrs: vec![],
+                    flags: TableFlags::empty(),
+                },
+                TableDefinition {
+                    name: allowed,
+                    addrs: vec![],
+                    flags: TableFlags::CONST,
+                },
+            ]
+        );
+    }
 }
@@ -8,7 +8,11 @@
 
 use zerocopy::FromZeros;
 
-use crate::{AnchorKind, Error, ErrorInternal, PoolAddr, Result, conversion::TryCopyTo, ffi};
+use crate::{
+    AddrFamily, AnchorKind, Error, ErrorInternal, PoolAddr, Result,
+    conversion::{CopyTo, TryCopyTo},
+    ffi,
+};
 use std::{
     fs::{File, OpenOptions},
     os::unix::io::RawFd,
@@ -38,6 +42,26 @@ pub fn add_pool_address<A: Into<PoolAddr>>(
     ioctl_guard!(ffi::pf_add_addr(fd, &mut pfioc_pooladdr))
 }
 
+/// Get the addresses in the address pool of a rule previously obtained with DIOCGETRULE.
+/// `pfioc_rule` must be the structure returned by that call, since its ticket is needed.
+pub fn get_pool_addrs(fd: RawFd, pfioc_rule: &ffi::pfvar::pfioc_rule) -> Result<Vec<PoolAddr>> {
+    let af = AddrFamily::try_from(pfioc_rule.rule.af)?;
+    let mut pfioc_pooladdr = ffi::pfvar::pfioc_pooladdr::new_zeroed();
+    pfioc_pooladdr.anchor = pfioc_rule.anchor;
+    pfioc_pooladdr.ticket = pfioc_rule.ticket;
+    pfioc_pooladdr.r_num = pfioc_rule.nr;
+    pfioc_pooladdr.r_action = pfioc_rule.rule.action;
+    ioctl_guard!(ffi::pf_get_addrs(fd, &mut pfioc_pooladdr))?;
+
+    (0..pfioc_pooladdr.nr)
+        .map(|nr| {
+            pfioc_pooladdr.nr = nr;
+            ioctl_guard!(ffi::pf_get_addr(fd, &mut pfioc_pooladdr))?;
+            PoolAddr::try_from_raw(&pfioc_pooladdr.addr, af)
+        })
+        .collect()
+}
+
 /// Get pool ticket
 pub fn get_pool_ticket(fd: RawFd) -> Result<u32> {
     let mut pfioc_pooladdr = ffi::pfvar::pfioc_pooladdr::new_zeroed();
@@ -59,3 +83,64 @@ pub fn copy_anchor_name(anchor: &str, destination: &mut [i8]) -> Result<()> {
         .try_copy_to(destination)
         .map_err(|reason| Error::from(ErrorInternal::InvalidAnchorName(reason)))
 }
+
+pub fn copy_table_name(name: &str, destination: &mut [i8]) -> Result<()> {
+    copy_name(name, destination, ErrorInternal::InvalidTableName)
+}
+
+/// Copies a name that must not be empty into a C string buffer. `invalid` creates the error
+/// returned if the name is empty or does not fit.
+fn copy_name(
+    name: &str,
+    destination: &mut [i8],
+    invalid: fn(&'static str) -> ErrorInternal,
+) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::from(invalid("Empty name")));
+    }
+    name.try_copy_to(destination)
+        .map_err(|reason| Error::from(invalid(reason)))
+}
+
+/// A name validated to fit, null terminated, into a C string buffer of `N` bytes, such as the
+/// table and interface names in PF structures. Storing the buffer itself keeps the types built on
+/// it `Copy`. See `impl_fixed_name!` for those types.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedName<const N: usize>([i8; N]);
+
+impl<const N: usize> FixedName<N> {
+    /// Returns a `FixedName` if `name` is not empty, does not contain any null byte and is
+    /// shorter than `N` bytes. `invalid` creates the error returned otherwise.
+    pub fn new(name: &str, invalid: fn(&'static str) -> ErrorInternal) -> Result<Self> {
+        let mut buf = [0; N];
+        copy_name(name, &mut buf, invalid)?;
+        Ok(FixedName(buf))
+    }
+
+    /// Reads a `FixedName` back from a raw, null terminated, C string.
+    pub fn try_from_raw(
+        name: &[std::os::raw::c_char],
+        invalid: fn(&'static str) -> ErrorInternal,
+    ) -> Result<Self> {
+        let name = crate::cstr_to_string(name).map_err(invalid)?;
+        FixedName::new(&name, invalid)
+    }
+
+    pub fn as_str(&self) -> &str {
+        let bytes: &[u8] = zerocopy::transmute_ref!(&self.0[..]);
+        let len = bytes
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(bytes.len());
+        // The name was validated as a `&str` when created, so it is valid UTF-8.
+        std::str::from_utf8(&bytes[..len]).expect("name must be valid UTF-8")
+    }
+}
+
+impl<const N: usize> CopyTo<[i8]> for FixedName<N> {
+    fn copy_to(&self, dst: &mut [i8]) {
+        self.as_str()
+            .try_copy_to(dst)
+            .expect("name must fit in the destination buffer");
+    }
+}
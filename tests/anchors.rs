@@ -45,3 +45,46 @@ test!(remove_filter_anchor {
     );
     assert_matches!(pf.try_remove_anchor(&anchor_name, pfctl::AnchorKind::Filter), Ok(()));
 });
+
+test!(list_anchors {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(pf.add_anchor(&anchor_name, pfctl::AnchorKind::Filter), Ok(()));
+
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Pass)
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(&anchor_name, &rule), Ok(()));
+
+    let anchors = pf.list_anchors("").unwrap();
+    let anchor = anchors.iter().find(|anchor| anchor.path() == anchor_name).unwrap();
+    assert_eq!(anchor.rule_count(pfctl::RulesetKind::Filter), 1);
+    assert_eq!(anchor.rule_count(pfctl::RulesetKind::Nat), 0);
+
+    assert!(
+        pf.anchor_tree()
+            .unwrap()
+            .iter()
+            .flat_map(pfctl::AnchorTree::iter)
+            .any(|anchor| anchor.path() == anchor_name)
+    );
+
+    pfcli::flush_rules(&anchor_name, pfcli::FlushOptions::Rules);
+    assert_matches!(pf.remove_anchor(&anchor_name, pfctl::AnchorKind::Filter), Ok(()));
+});
+
+test!(list_anchor_hooks {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(pf.add_anchor(&anchor_name, pfctl::AnchorKind::Redirect), Ok(()));
+
+    let hooks = pf.list_anchor_hooks(pfctl::AnchorKind::Redirect).unwrap();
+    let hook = hooks.iter().find(|hook| hook.anchor() == anchor_name).unwrap();
+    assert_eq!(hook.direction(), pfctl::Direction::Any);
+    assert_eq!(hook.interface(), &pfctl::Interface::Any);
+    assert!(!hook.quick());
+    assert!(hooks.windows(2).all(|pair| pair[0].nr() < pair[1].nr()));
+
+    assert_matches!(pf.remove_anchor(&anchor_name, pfctl::AnchorKind::Redirect), Ok(()));
+});
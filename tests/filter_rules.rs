@@ -4,7 +4,7 @@ mod helper;
 
 use crate::helper::pfcli;
 use assert_matches::assert_matches;
-use std::net::Ipv4Addr;
+use std::net::{Ipv4Addr, Ipv6Addr};
 
 static ANCHOR_NAME: &str = "pfctl-rs.integration.testing.filter-rules";
 
@@ -83,6 +83,196 @@ test!(drop_by_ip_rule {
     );
 });
 
+test!(drop_by_table_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let table = pfctl::TableName::new("blocklist").unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .from(table)
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &["block drop from <blocklist> to any"]
+    );
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), &[rule]);
+});
+
+test!(drop_by_interface_network_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .af(pfctl::AddrFamily::Ipv4)
+        .from(pfctl::Ip::interface("lo0", pfctl::InterfaceAddrModifier::Network).unwrap())
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &["block drop inet from (lo0:network) to any"]
+    );
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), &[rule]);
+});
+
+test!(drop_by_negated_network_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let lan = pfctl::ipnetwork::IpNetwork::new(Ipv4Addr::new(10, 0, 0, 0).into(), 8).unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .af(pfctl::AddrFamily::Ipv4)
+        .from(pfctl::Endpoint::not(lan, pfctl::Port::Any))
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &["block drop inet from ! 10.0.0.0/8 to any"]
+    );
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), &[rule]);
+});
+
+test!(drop_by_range_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let range = pfctl::Ip::Range(
+        Ipv4Addr::new(10, 0, 0, 10).into(),
+        Ipv4Addr::new(10, 0, 0, 50).into(),
+    );
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .af(pfctl::AddrFamily::Ipv4)
+        .from(range)
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &["block drop inet from 10.0.0.10 - 10.0.0.50 to any"]
+    );
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), &[rule]);
+});
+
+test!(drop_spoofed_rules {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rules = [pfctl::Ip::NoRoute, pfctl::Ip::UrpfFailed].map(|ip| {
+        pfctl::FilterRuleBuilder::default()
+            .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+            .direction(pfctl::Direction::In)
+            .from(ip)
+            .build()
+            .unwrap()
+    });
+    for rule in &rules {
+        assert_matches!(pf.add_rule(ANCHOR_NAME, rule), Ok(()));
+    }
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &[
+            "block drop in from no-route to any",
+            "block drop in from urpf-failed to any"
+        ]
+    );
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), &rules);
+});
+
+test!(drop_on_negated_interface_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .interface(pfctl::Interface::not("utun3"))
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_eq!(pfcli::get_rules(ANCHOR_NAME), &["block drop on ! utun3 all"]);
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), &[rule]);
+});
+
+test!(drop_by_expanded_lists_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .proto_any_of([pfctl::Proto::Tcp, pfctl::Proto::Udp])
+        .from(Ipv4Addr::new(10, 0, 0, 1))
+        .to_any_of([
+            pfctl::Endpoint::new(Ipv4Addr::new(10, 0, 0, 2), 53),
+            pfctl::Endpoint::new(Ipv6Addr::LOCALHOST, 53),
+        ])
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &[
+            "block drop inet proto tcp from 10.0.0.1 to 10.0.0.2 port = 53",
+            "block drop inet proto udp from 10.0.0.1 to 10.0.0.2 port = 53",
+        ]
+    );
+    let fingerprints = |rules: &[pfctl::FilterRule]| -> Vec<pfctl::RuleFingerprint> {
+        rules.iter().map(|rule| rule.fingerprint().unwrap()).collect()
+    };
+    let expanded: Vec<pfctl::FilterRule> =
+        rule.expand().unwrap().into_iter().map(Into::into).collect();
+    assert_eq!(
+        fingerprints(&pf.get_filter_rules(ANCHOR_NAME).unwrap()),
+        fingerprints(&expanded)
+    );
+});
+
+test!(drop_by_port_lists_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .proto_any_of([pfctl::Proto::Tcp, pfctl::Proto::Udp])
+        .to_any_of([Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)])
+        .to_port_any_of([53, 853])
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &[
+            "block drop inet proto tcp from any to 10.0.0.1 port = 53",
+            "block drop inet proto tcp from any to 10.0.0.1 port = 853",
+            "block drop inet proto tcp from any to 10.0.0.2 port = 53",
+            "block drop inet proto tcp from any to 10.0.0.2 port = 853",
+            "block drop inet proto udp from any to 10.0.0.1 port = 53",
+            "block drop inet proto udp from any to 10.0.0.1 port = 853",
+            "block drop inet proto udp from any to 10.0.0.2 port = 53",
+            "block drop inet proto udp from any to 10.0.0.2 port = 853",
+        ]
+    );
+    let fingerprints = |rules: &[pfctl::FilterRule]| -> Vec<pfctl::RuleFingerprint> {
+        rules.iter().map(|rule| rule.fingerprint().unwrap()).collect()
+    };
+    let expanded: Vec<pfctl::FilterRule> =
+        rule.expand().unwrap().into_iter().map(Into::into).collect();
+    assert_eq!(
+        fingerprints(&pf.get_filter_rules(ANCHOR_NAME).unwrap()),
+        fingerprints(&expanded)
+    );
+});
+
+test!(drop_by_other_proto_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .proto_any_of([pfctl::Proto::GRE, pfctl::Proto::ESP, pfctl::Proto::Other(200)])
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &[
+            "block drop proto gre all",
+            "block drop proto esp all",
+            "block drop proto 200 all",
+        ]
+    );
+    let expanded: Vec<pfctl::FilterRule> =
+        rule.expand().unwrap().into_iter().map(Into::into).collect();
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), expanded);
+});
+
 test!(drop_by_port_rule {
     let mut pf = pfctl::PfCtl::new().unwrap();
     let rule = pfctl::FilterRuleBuilder::default()
@@ -291,3 +481,250 @@ test!(logging {
         &["block drop log (all, user) all"]
     );
 });
+
+test!(get_filter_rules {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule1 = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::ReturnRst))
+        .direction(pfctl::Direction::In)
+        .quick(true)
+        .interface("utun0")
+        .proto(pfctl::Proto::Tcp)
+        .af(pfctl::AddrFamily::Ipv4)
+        .from(pfctl::Endpoint::new(
+            pfctl::ipnetwork::IpNetwork::new(Ipv4Addr::new(10, 0, 0, 0).into(), 8).unwrap(),
+            pfctl::Port::Range(3000, 4000, pfctl::PortRangeModifier::Inclusive),
+        ))
+        .to(pfctl::Port::One(8080, pfctl::PortUnaryModifier::Equal))
+        .label("get-filter-rules")
+        .build()
+        .unwrap();
+    let rule2 = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Pass)
+        .direction(pfctl::Direction::Out)
+        .proto(pfctl::Proto::Tcp)
+        .keep_state(pfctl::StatePolicy::Keep)
+        .tcp_flags(([pfctl::TcpFlag::Syn], [pfctl::TcpFlag::Syn, pfctl::TcpFlag::Ack]))
+        .user(501u32)
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule1), Ok(()));
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule2), Ok(()));
+
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), [rule1, rule2]);
+});
+
+test!(get_filter_rules_with_route {
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Pass)
+        .direction(pfctl::Direction::Out)
+        .route(pfctl::Route::route_to(pfctl::PoolAddr::new(
+            "lo0",
+            Ipv4Addr::new(127, 0, 0, 1),
+        )))
+        .af(pfctl::AddrFamily::Ipv4)
+        .from(Ipv4Addr::new(1, 2, 3, 4))
+        .build()
+        .unwrap();
+
+    let mut change = pfctl::AnchorChange::new();
+    change.set_filter_rules(vec![rule.clone()]);
+    let mut trans = pfctl::Transaction::new();
+    trans.add_change(ANCHOR_NAME, change);
+    assert_matches!(trans.commit(), Ok(()));
+
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_eq!(pf.get_filter_rules(ANCHOR_NAME).unwrap(), [rule]);
+});
+
+test!(get_rule_stats {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule1 = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Pass)
+        .label("first")
+        .build()
+        .unwrap();
+    let rule2 = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .label("second")
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule1), Ok(()));
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule2), Ok(()));
+
+    let stats = pf.get_rule_stats(ANCHOR_NAME, pfctl::AnchorKind::Filter).unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!((stats[0].nr(), stats[0].label()), (0, "first"));
+    assert_eq!((stats[1].nr(), stats[1].label()), (1, "second"));
+
+    pf.get_and_clear_rule_stats(ANCHOR_NAME, pfctl::AnchorKind::Filter).unwrap();
+    for stats in pf.get_rule_stats(ANCHOR_NAME, pfctl::AnchorKind::Filter).unwrap() {
+        assert_eq!(stats.evaluations(), 0);
+        assert_eq!(stats.packets_in() + stats.packets_out(), 0);
+        assert_eq!(stats.bytes_in() + stats.bytes_out(), 0);
+    }
+});
+
+fn labeled_drop_rule(label: &str) -> pfctl::FilterRule {
+    pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .label(label)
+        .build()
+        .unwrap()
+}
+
+test!(insert_rule_at_position {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let positions = [
+        ("b", pfctl::RulePosition::Tail),
+        ("a", pfctl::RulePosition::Head),
+        ("d", pfctl::RulePosition::After(1)),
+        ("c", pfctl::RulePosition::Before(2)),
+    ];
+    for (label, position) in positions {
+        let rule = labeled_drop_rule(label);
+        assert_matches!(pf.insert_rule(ANCHOR_NAME, &rule, position), Ok(()));
+    }
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &[
+            "block drop all label \"a\"",
+            "block drop all label \"b\"",
+            "block drop all label \"c\"",
+            "block drop all label \"d\"",
+        ]
+    );
+});
+
+test!(remove_and_replace_rule_at {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    for label in ["a", "b", "c"] {
+        assert_matches!(pf.add_rule(ANCHOR_NAME, &labeled_drop_rule(label)), Ok(()));
+    }
+
+    assert_matches!(pf.remove_rule_at(ANCHOR_NAME, pfctl::AnchorKind::Filter, 1), Ok(()));
+    assert_matches!(pf.replace_rule_at(ANCHOR_NAME, &labeled_drop_rule("d"), 1), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &["block drop all label \"a\"", "block drop all label \"d\""]
+    );
+
+    assert_matches!(
+        pf.remove_rule_at(ANCHOR_NAME, pfctl::AnchorKind::Filter, 2),
+        Err(e) if e.kind() == pfctl::ErrorKind::Ioctl
+    );
+});
+
+test!(remove_matching_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    for label in ["a", "b", "a", "c"] {
+        assert_matches!(pf.add_rule(ANCHOR_NAME, &labeled_drop_rule(label)), Ok(()));
+    }
+
+    assert_matches!(
+        pf.remove_matching_rule(ANCHOR_NAME, &labeled_drop_rule("b"), pfctl::RemoveMode::First),
+        Ok(1)
+    );
+    assert_matches!(
+        pf.remove_matching_rule(ANCHOR_NAME, &labeled_drop_rule("a"), pfctl::RemoveMode::All),
+        Ok(2)
+    );
+    assert_eq!(pfcli::get_rules(ANCHOR_NAME), &["block drop all label \"c\""]);
+
+    assert_matches!(
+        pf.remove_matching_rule(ANCHOR_NAME, &labeled_drop_rule("a"), pfctl::RemoveMode::First),
+        Err(e) if e.kind() == pfctl::ErrorKind::RuleDoesNotExist
+    );
+});
+
+test!(remove_matching_list_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .proto_any_of([pfctl::Proto::Tcp, pfctl::Proto::Udp])
+        .to_any_of([Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)])
+        .to_port_any_of([53, 853])
+        .build()
+        .unwrap();
+    let udp_rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Drop(pfctl::DropAction::Drop))
+        .proto(pfctl::Proto::Udp)
+        .to(pfctl::Endpoint::new(Ipv4Addr::new(10, 0, 0, 1), 53))
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+    assert_matches!(pf.add_rule(ANCHOR_NAME, &rule), Ok(()));
+
+    assert_matches!(
+        pf.remove_matching_rule(ANCHOR_NAME, &rule, pfctl::RemoveMode::First),
+        Ok(8)
+    );
+    assert_eq!(pfcli::get_rules(ANCHOR_NAME).len(), 8);
+    assert_matches!(
+        pf.remove_matching_rule(ANCHOR_NAME, &udp_rule, pfctl::RemoveMode::First),
+        Ok(1)
+    );
+    assert_matches!(
+        pf.remove_matching_rule(ANCHOR_NAME, &rule, pfctl::RemoveMode::First),
+        Err(e) if e.kind() == pfctl::ErrorKind::RuleDoesNotExist
+    );
+    assert_eq!(pfcli::get_rules(ANCHOR_NAME).len(), 7);
+});
+
+test!(add_rules {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rules = [labeled_drop_rule("a"), labeled_drop_rule("b"), labeled_drop_rule("c")];
+    assert_matches!(pf.add_rules(ANCHOR_NAME, &rules), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &[
+            "block drop all label \"a\"",
+            "block drop all label \"b\"",
+            "block drop all label \"c\"",
+        ]
+    );
+});
+
+test!(add_rules_reports_failing_index {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let invalid_rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Pass)
+        .af(pfctl::AddrFamily::Ipv4)
+        .to(Ipv6Addr::LOCALHOST)
+        .build()
+        .unwrap();
+    let rules = [labeled_drop_rule("a"), invalid_rule, labeled_drop_rule("c")];
+
+    let error = pf.add_rules(ANCHOR_NAME, &rules).unwrap_err();
+    assert_eq!(error.rule_index(), Some(1));
+    assert_eq!(error.kind(), pfctl::ErrorKind::InvalidRuleCombination);
+    assert_eq!(pfcli::get_rules(ANCHOR_NAME), &["block drop all label \"a\""]);
+});
+
+test!(add_rule_of_any_kind {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule = pfctl::Rule::from(labeled_drop_rule("b"));
+    assert_matches!(pf.add(ANCHOR_NAME, &rule), Ok(()));
+    let rule = pfctl::Rule::from(labeled_drop_rule("a"));
+    assert_matches!(pf.insert(ANCHOR_NAME, &rule, pfctl::RulePosition::Head), Ok(()));
+    assert_eq!(
+        pfcli::get_rules(ANCHOR_NAME),
+        &["block drop all label \"a\"", "block drop all label \"b\""]
+    );
+});
+
+test!(fingerprints_survive_reload {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rules = [labeled_drop_rule("a"), labeled_drop_rule("b")];
+    assert_matches!(pf.add_rules(ANCHOR_NAME, &rules), Ok(()));
+
+    let fingerprints: Vec<pfctl::RuleFingerprint> =
+        rules.iter().map(|rule| rule.fingerprint().unwrap()).collect();
+    let read_back: Vec<pfctl::RuleFingerprint> = pf
+        .get_filter_rules(ANCHOR_NAME)
+        .unwrap()
+        .iter()
+        .map(|rule| rule.fingerprint().unwrap())
+        .collect();
+    assert_eq!(read_back, fingerprints);
+});
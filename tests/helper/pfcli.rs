@@ -74,6 +74,11 @@ pub fn get_nat_rules(anchor_name: &str) -> Vec<String> {
     get_rules_internal(anchor_name, "nat")
 }
 
+/// Get names of tables in anchor
+pub fn get_tables(anchor_name: &str) -> Vec<String> {
+    get_rules_internal(anchor_name, "Tables")
+}
+
 /// Get global table of states
 pub fn get_all_states() -> Vec<String> {
     let output = get_command()
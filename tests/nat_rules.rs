@@ -102,3 +102,72 @@ test!(add_nonat_rule_ipv6 {
         &["no nat inet6 from any to ::1 port = 1234"]
     );
 });
+
+test!(get_nat_rules {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule1 = pfctl::NatRuleBuilder::default()
+        .action(pfctl::NatRuleAction::Nat {
+            nat_to: pfctl::Endpoint::new(
+                Ipv4Addr::new(127, 0, 0, 2),
+                pfctl::Port::Range(32768, 49151, pfctl::PortRangeModifier::Inclusive),
+            )
+            .into(),
+        })
+        .interface("lo0")
+        .af(pfctl::AddrFamily::Ipv4)
+        .to(pfctl::Endpoint::new(Ipv4Addr::new(127, 0, 0, 1), 1234))
+        .build()
+        .unwrap();
+    let rule2 = pfctl::NatRuleBuilder::default()
+        .action(pfctl::NatRuleAction::NoNat)
+        .af(pfctl::AddrFamily::Ipv6)
+        .to(pfctl::Endpoint::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 1234))
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_nat_rule(ANCHOR_NAME, &rule1), Ok(()));
+    assert_matches!(pf.add_nat_rule(ANCHOR_NAME, &rule2), Ok(()));
+
+    assert_eq!(pf.get_nat_rules(ANCHOR_NAME).unwrap(), [rule1, rule2]);
+});
+
+test!(insert_and_replace_nat_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(pf.add_nat_rule(ANCHOR_NAME, &nat_rule_ipv4()), Ok(()));
+    assert_matches!(
+        pf.insert_nat_rule(ANCHOR_NAME, &nonat_rule_ipv4(), pfctl::RulePosition::Head),
+        Ok(())
+    );
+    assert_eq!(
+        pfcli::get_nat_rules(ANCHOR_NAME),
+        &[
+            "no nat inet from any to 127.0.0.1 port = 1234",
+            "nat inet from any to 127.0.0.1 port = 1234 -> 127.0.0.2",
+        ]
+    );
+
+    assert_matches!(pf.replace_nat_rule_at(ANCHOR_NAME, &nat_rule_ipv6(), 1), Ok(()));
+    assert_matches!(pf.remove_rule_at(ANCHOR_NAME, pfctl::AnchorKind::Nat, 0), Ok(()));
+    assert_eq!(
+        pfcli::get_nat_rules(ANCHOR_NAME),
+        &["nat inet6 from any to ::1 port = 1234 -> ::2"]
+    );
+});
+
+test!(remove_matching_nat_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(pf.add_nat_rule(ANCHOR_NAME, &nat_rule_ipv4()), Ok(()));
+    assert_matches!(pf.add_nat_rule(ANCHOR_NAME, &nat_rule_ipv6()), Ok(()));
+
+    assert_matches!(
+        pf.remove_matching_nat_rule(ANCHOR_NAME, &nat_rule_ipv4(), pfctl::RemoveMode::First),
+        Ok(1)
+    );
+    assert_eq!(
+        pfcli::get_nat_rules(ANCHOR_NAME),
+        &["nat inet6 from any to ::1 port = 1234 -> ::2"]
+    );
+    assert_matches!(
+        pf.remove_matching_nat_rule(ANCHOR_NAME, &nonat_rule_ipv6(), pfctl::RemoveMode::All),
+        Err(e) if e.kind() == pfctl::ErrorKind::RuleDoesNotExist
+    );
+});
@@ -91,3 +91,40 @@ test!(add_redirect_rule_on_interface {
         &["rdr log on lo0 inet from 1.2.3.4 to any -> any port 1237"]
     );
 });
+
+test!(get_redirect_rules {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rule1 = pfctl::RedirectRuleBuilder::default()
+        .action(pfctl::RedirectRuleAction::Redirect)
+        .interface("lo0")
+        .proto(pfctl::Proto::Tcp)
+        .af(pfctl::AddrFamily::Ipv4)
+        .to(pfctl::Endpoint::new(Ipv4Addr::new(127, 0, 0, 1), 3000))
+        .redirect_to(pfctl::Endpoint::new(Ipv4Addr::new(127, 0, 0, 1), 4000))
+        .build()
+        .unwrap();
+    let rule2 = pfctl::RedirectRuleBuilder::default()
+        .action(pfctl::RedirectRuleAction::Redirect)
+        .af(pfctl::AddrFamily::Ipv6)
+        .to(pfctl::Endpoint::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 3000))
+        .redirect_to(pfctl::Endpoint::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 4000))
+        .build()
+        .unwrap();
+    assert_matches!(pf.add_redirect_rule(ANCHOR_NAME, &rule1), Ok(()));
+    assert_matches!(pf.add_redirect_rule(ANCHOR_NAME, &rule2), Ok(()));
+
+    assert_eq!(pf.get_redirect_rules(ANCHOR_NAME).unwrap(), [rule1, rule2]);
+});
+
+test!(add_redirect_rules {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    let rules = [redirect_rule_ipv4(), redirect_rule_ipv6()];
+    assert_matches!(pf.add_redirect_rules(ANCHOR_NAME, &rules), Ok(()));
+    assert_eq!(
+        pfcli::get_nat_rules(ANCHOR_NAME),
+        &[
+            "rdr inet from any to 127.0.0.1 port = 3000 -> 127.0.0.1 port 4000",
+            "rdr inet6 from any to ::1 port = 3000 -> ::1 port 4000",
+        ]
+    );
+});
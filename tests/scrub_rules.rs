@@ -70,3 +70,15 @@ test!(add_no_scrub_rule {
         &["no scrub all"]
     );
 });
+
+test!(remove_matching_scrub_rule {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(pf.add_scrub_rule(ANCHOR_NAME, &scrub_rule()), Ok(()));
+    assert_matches!(pf.add_scrub_rule(ANCHOR_NAME, &no_scrub_rule()), Ok(()));
+
+    assert_matches!(
+        pf.remove_matching_scrub_rule(ANCHOR_NAME, &scrub_rule(), pfctl::RemoveMode::All),
+        Ok(1)
+    );
+    assert_eq!(pfcli::get_rules(ANCHOR_NAME), &["no scrub all"]);
+});
@@ -0,0 +1,252 @@
+#[macro_use]
+#[allow(dead_code)]
+mod helper;
+
+use crate::helper::pfcli;
+use assert_matches::assert_matches;
+use pfctl::ipnetwork::IpNetwork;
+use std::{
+    net::{IpAddr, Ipv6Addr},
+    thread,
+    time::Duration,
+};
+use uuid::Uuid;
+
+fn unique_anchor() -> String {
+    format!("pfctl-rs.integration.testing.{}", Uuid::new_v4().simple())
+}
+
+fn before_each() {}
+fn after_each() {}
+
+test!(create_and_delete_table {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+
+    assert_matches!(
+        pf.create_table(&anchor_name, "test-table", pfctl::TableFlags::PERSIST),
+        Ok(true)
+    );
+    assert_matches!(
+        pf.create_table(&anchor_name, "test-table", pfctl::TableFlags::PERSIST),
+        Ok(false)
+    );
+    assert_eq!(pfcli::get_tables(&anchor_name), &["test-table"]);
+
+    assert_matches!(pf.delete_table(&anchor_name, "test-table"), Ok(true));
+    assert_matches!(pf.delete_table(&anchor_name, "test-table"), Ok(false));
+    assert!(pfcli::get_tables(&anchor_name).is_empty());
+});
+
+test!(list_tables {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+
+    let flags = pfctl::TableFlags::PERSIST | pfctl::TableFlags::CONST;
+    assert_matches!(pf.create_table(&anchor_name, "first", flags), Ok(true));
+    assert_matches!(
+        pf.create_table(&anchor_name, "second", pfctl::TableFlags::PERSIST),
+        Ok(true)
+    );
+
+    let tables = pf.list_tables(&anchor_name).unwrap();
+    assert_eq!(tables.len(), 2);
+    let first = tables.iter().find(|table| table.name() == "first").unwrap();
+    assert_eq!(first.anchor(), anchor_name);
+    assert!(first.flags().contains(flags | pfctl::TableFlags::ACTIVE));
+    assert_eq!(first.addr_count(), 0);
+    let second = tables.iter().find(|table| table.name() == "second").unwrap();
+    assert!(!second.flags().contains(pfctl::TableFlags::CONST));
+
+    assert_matches!(pf.delete_table(&anchor_name, "first"), Ok(true));
+    assert_matches!(pf.delete_table(&anchor_name, "second"), Ok(true));
+});
+
+test!(invalid_table_name {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+
+    assert_matches!(
+        pf.create_table(&anchor_name, "", pfctl::TableFlags::PERSIST),
+        Err(e) if e.kind() == pfctl::ErrorKind::InvalidTableName
+    );
+    assert_matches!(
+        pf.create_table(&anchor_name, &"a".repeat(32), pfctl::TableFlags::PERSIST),
+        Err(e) if e.kind() == pfctl::ErrorKind::InvalidTableName
+    );
+});
+
+test!(invalid_table_flags {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+
+    assert_matches!(
+        pf.create_table(
+            &anchor_name,
+            "test-table",
+            pfctl::TableFlags::PERSIST | pfctl::TableFlags::ACTIVE
+        ),
+        Err(e) if e.kind() == pfctl::ErrorKind::InvalidTableFlags
+    );
+    assert!(pfcli::get_tables(&anchor_name).is_empty());
+});
+
+test!(add_delete_and_get_table_addrs {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(
+        pf.create_table(&anchor_name, "addrs", pfctl::TableFlags::PERSIST),
+        Ok(true)
+    );
+
+    let lan: IpNetwork = "10.0.0.0/8".parse().unwrap();
+    let gateway: IpNetwork = "10.0.0.1/32".parse().unwrap();
+    let addrs = [
+        pfctl::TableAddr::new(lan),
+        pfctl::TableAddr::negated(gateway),
+        pfctl::TableAddr::from(Ipv6Addr::LOCALHOST),
+    ];
+    assert_matches!(pf.table_add_addrs(&anchor_name, "addrs", addrs), Ok(3));
+    assert_matches!(pf.table_add_addrs(&anchor_name, "addrs", [lan]), Ok(0));
+
+    let mut read_back = pf.table_get_addrs(&anchor_name, "addrs").unwrap();
+    read_back.sort_by_key(|addr| addr.network());
+    let mut expected = addrs.to_vec();
+    expected.sort_by_key(|addr| addr.network());
+    assert_eq!(read_back, expected);
+
+    assert_matches!(pf.table_delete_addrs(&anchor_name, "addrs", [lan]), Ok(1));
+    assert_matches!(pf.table_delete_addrs(&anchor_name, "addrs", [lan]), Ok(0));
+    assert_matches!(pf.table_clear(&anchor_name, "addrs"), Ok(2));
+    assert!(pf.table_get_addrs(&anchor_name, "addrs").unwrap().is_empty());
+
+    assert_matches!(pf.delete_table(&anchor_name, "addrs"), Ok(true));
+});
+
+test!(replace_table_addrs {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(
+        pf.create_table(&anchor_name, "relays", pfctl::TableFlags::PERSIST),
+        Ok(true)
+    );
+
+    let first: IpNetwork = "192.0.2.1/32".parse().unwrap();
+    let second: IpNetwork = "192.0.2.2/32".parse().unwrap();
+    let third: IpNetwork = "192.0.2.3/32".parse().unwrap();
+    assert_matches!(pf.table_add_addrs(&anchor_name, "relays", [first, second]), Ok(2));
+
+    let changes = pf
+        .table_replace_addrs(
+            &anchor_name,
+            "relays",
+            [
+                pfctl::TableAddr::negated(first),
+                pfctl::TableAddr::new(third),
+            ],
+        )
+        .unwrap();
+    assert_eq!(changes.added(), 1);
+    assert_eq!(changes.deleted(), 1);
+    assert_eq!(changes.changed(), 1);
+
+    let tables = pf.list_tables(&anchor_name).unwrap();
+    assert_eq!(tables[0].addr_count(), 2);
+
+    assert_matches!(pf.delete_table(&anchor_name, "relays"), Ok(true));
+});
+
+test!(table_addr_stats_and_expire {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(
+        pf.create_table(&anchor_name, "overload", pfctl::TableFlags::PERSIST),
+        Ok(true)
+    );
+
+    let addrs: [IpNetwork; 2] = ["198.51.100.1/32".parse().unwrap(), "2001:db8::/32".parse().unwrap()];
+    assert_matches!(pf.table_add_addrs(&anchor_name, "overload", addrs), Ok(2));
+
+    let stats = pf.table_get_addr_stats(&anchor_name, "overload").unwrap();
+    assert_eq!(stats.len(), 2);
+    for stats in &stats {
+        assert!(addrs.contains(&stats.addr().network()));
+        assert_eq!(stats.passed().packets_in(), 0);
+        assert_eq!(stats.blocked().bytes_out(), 0);
+        assert!(stats.zeroed_at().elapsed().unwrap() < Duration::from_secs(60));
+    }
+
+    assert_matches!(pf.table_clear_addr_stats(&anchor_name, "overload", addrs), Ok(2));
+
+    assert_matches!(
+        pf.table_expire(&anchor_name, "overload", Duration::from_secs(3600)),
+        Ok(0)
+    );
+    thread::sleep(Duration::from_secs(2));
+    assert_matches!(pf.table_expire(&anchor_name, "overload", Duration::from_secs(1)), Ok(2));
+    assert!(pf.table_get_addrs(&anchor_name, "overload").unwrap().is_empty());
+
+    assert_matches!(pf.delete_table(&anchor_name, "overload"), Ok(true));
+});
+
+test!(test_table_addrs {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+    assert_matches!(
+        pf.create_table(&anchor_name, "blocked", pfctl::TableFlags::PERSIST),
+        Ok(true)
+    );
+
+    let entries = [
+        pfctl::TableAddr::new("10.0.0.0/8".parse().unwrap()),
+        pfctl::TableAddr::negated("10.1.0.0/16".parse().unwrap()),
+    ];
+    assert_matches!(pf.table_add_addrs(&anchor_name, "blocked", entries), Ok(2));
+
+    let in_table = IpAddr::from([10, 2, 3, 4]);
+    let negated = IpAddr::from([10, 1, 2, 3]);
+    let outside = IpAddr::from([192, 168, 1, 1]);
+    assert_eq!(
+        pf.table_test(&anchor_name, "blocked", &[in_table, negated, outside]).unwrap(),
+        &[(in_table, true), (negated, false), (outside, false)]
+    );
+
+    assert_matches!(pf.delete_table(&anchor_name, "blocked"), Ok(true));
+});
+
+struct FixedResolver(Vec<IpAddr>);
+
+impl pfctl::Resolver for FixedResolver {
+    fn resolve(&self, _host: &str) -> std::io::Result<Vec<IpAddr>> {
+        Ok(self.0.clone())
+    }
+}
+
+test!(refresh_host_table {
+    let anchor_name = unique_anchor();
+    let mut pf = pfctl::PfCtl::new().unwrap();
+
+    let addr = IpAddr::from([192, 0, 2, 10]);
+    let mut host_table = pfctl::HostTable::with_resolver(
+        &anchor_name,
+        pfctl::TableName::new("hosts").unwrap(),
+        ["api.example.com"],
+        FixedResolver(vec![addr]),
+    );
+    let update = host_table.refresh_if_due(&mut pf).unwrap().unwrap();
+    assert_eq!(update.added(), &[addr]);
+    assert_matches!(host_table.refresh_if_due(&mut pf), Ok(None));
+
+    assert_eq!(
+        pf.table_get_addrs(&anchor_name, "hosts").unwrap(),
+        &[pfctl::TableAddr::from(addr)]
+    );
+    assert!(host_table.refresh(&mut pf).unwrap().is_empty());
+
+    assert_matches!(pf.table_clear(&anchor_name, "hosts"), Ok(1));
+    let update = host_table.refresh(&mut pf).unwrap();
+    assert!(update.is_empty());
+    assert_eq!(update.table_changes().added(), 1);
+
+    assert_matches!(pf.delete_table(&anchor_name, "hosts"), Ok(true));
+});
@@ -256,3 +256,78 @@ test!(replace_one_ruleset_in_many_anchors {
     verify_scrub_rules(ANCHOR4_NAME);
     verify_redirect_marker(ANCHOR2_NAME);
 });
+
+// Test that pushing rules of mixed kinds sorts them into the right rulesets
+test!(push_rules_of_all_kinds {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+
+    let rules: Vec<pfctl::Rule> = get_filter_rules()
+        .into_iter()
+        .map(pfctl::Rule::from)
+        .chain(get_nat_rules().into_iter().map(pfctl::Rule::from))
+        .chain(get_redirect_rules().into_iter().map(pfctl::Rule::from))
+        .chain(get_scrub_rules().into_iter().map(pfctl::Rule::from))
+        .collect();
+
+    let mut change = pfctl::AnchorChange::new();
+    for rule in rules {
+        change.push(rule);
+    }
+    pf.set_rules(ANCHOR1_NAME, change).unwrap();
+
+    verify_filter_rules(ANCHOR1_NAME);
+    verify_scrub_rules(ANCHOR1_NAME);
+    verify_nat_rules(ANCHOR1_NAME);
+    verify_redirect_rules(ANCHOR1_NAME);
+});
+
+// Test that a table and the rule referring to it are committed together
+test!(set_table_with_rules {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+
+    let relays = pfctl::TableName::new("relays").unwrap();
+    let rule = pfctl::FilterRuleBuilder::default()
+        .action(pfctl::FilterRuleAction::Pass)
+        .to(relays)
+        .build()
+        .unwrap();
+    let addrs = [Ipv4Addr::new(192, 0, 2, 1), Ipv4Addr::new(192, 0, 2, 2)];
+
+    let mut change = pfctl::AnchorChange::new();
+    change.set_filter_rules(vec![rule]);
+    change.set_table(relays, addrs, pfctl::TableFlags::empty());
+    pf.set_rules(ANCHOR1_NAME, change).unwrap();
+
+    assert_eq!(
+        pfcli::get_rules(ANCHOR1_NAME),
+        &["pass from any to <relays>"]
+    );
+    let tables = pf.list_tables(ANCHOR1_NAME).unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].name(), "relays");
+    assert!(tables[0].flags().contains(pfctl::TableFlags::REFERENCED));
+    assert_eq!(tables[0].addr_count(), 2);
+
+    // Redefining the table replaces its contents
+    let mut change = pfctl::AnchorChange::new();
+    change.set_table(relays, [Ipv4Addr::new(192, 0, 2, 3)], pfctl::TableFlags::empty());
+    pf.set_rules(ANCHOR1_NAME, change).unwrap();
+    assert_eq!(
+        pf.table_get_addrs(ANCHOR1_NAME, "relays").unwrap(),
+        &[pfctl::TableAddr::from(Ipv4Addr::new(192, 0, 2, 3))]
+    );
+});
+
+// Test that a table with flags only PF can set fails the transaction before reaching PF
+test!(set_table_with_invalid_flags {
+    let mut pf = pfctl::PfCtl::new().unwrap();
+
+    let relays = pfctl::TableName::new("relays").unwrap();
+    let mut change = pfctl::AnchorChange::new();
+    change.set_table(relays, [Ipv4Addr::new(192, 0, 2, 1)], pfctl::TableFlags::REFERENCED);
+    assert_matches!(
+        pf.set_rules(ANCHOR1_NAME, change),
+        Err(e) if e.kind() == pfctl::ErrorKind::InvalidTableFlags
+    );
+    assert!(pf.list_tables(ANCHOR1_NAME).unwrap().is_empty());
+});